@@ -0,0 +1,310 @@
+//! Benchmarks along the lines of upstream `bench.c`: a 16-byte password and
+//! salt hashed into a 16-byte tag, for each `Argon2_type` and a range of
+//! memory sizes, pass counts and lane counts, with one thread per lane, and
+//! for thread counts with a fixed number of lanes.
+//!
+//! Throughput is given per byte of memory. On x86 and x86_64, setting
+//! `ARGON2_SYS_BENCH_CYCLES=1` runs every benchmark a second time under
+//! `cycles/`, counting `rdtsc` cycles instead of wall time, so that
+//! throughput is given in cycles per byte.
+//!
+//! The memory sizes stop at 64 MiB by default. `ARGON2_SYS_BENCH_LARGE=1`
+//! adds the 256 MiB and 1 GiB cases of `bench.c`, which take far longer
+//! than the rest of the suite together. Pass a filter to run part of it:
+//!
+//! ```sh
+//! cargo bench -- m_cost/argon2id
+//! cargo bench -- 'lanes/.*/65536'
+//! cargo bench -- threads
+//! cargo bench --features dispatch -- backend
+//! ARGON2_SYS_BENCH_LARGE=1 cargo bench -- m_cost/argon2id
+//! ARGON2_SYS_BENCH_CYCLES=1 cargo bench -- cycles/t_cost
+//! ```
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use argon2_sys::{
+    Argon2_d, Argon2_i, Argon2_id, Argon2_type, Hasher, Params, ARGON2_VERSION_NUMBER,
+};
+use criterion::measurement::Measurement;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion,
+    SamplingMode, Throughput,
+};
+use std::env;
+
+const TYPES: [(Argon2_type, &str); 3] = [
+    (Argon2_i, "argon2i"),
+    (Argon2_d, "argon2d"),
+    (Argon2_id, "argon2id"),
+];
+
+/// From 1 MiB to 1 GiB, in KiB. Only the first `DEFAULT_M_COSTS` are run
+/// without `ARGON2_SYS_BENCH_LARGE`.
+const M_COSTS: [u32; 6] = [1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 18, 1 << 20];
+const DEFAULT_M_COSTS: usize = 4;
+
+/// Whether the environment variable `name` is set to anything but `0`.
+fn env_flag(name: &str) -> bool {
+    env::var(name).map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+fn params(ty: Argon2_type, t_cost: u32, m_cost: u32, lanes: u32) -> Params {
+    Params {
+        ty,
+        version: ARGON2_VERSION_NUMBER,
+        t_cost,
+        m_cost,
+        lanes,
+        hash_len: 16,
+    }
+}
+
+fn hash(params: &Params) {
+    let hasher = Hasher::new(*params);
+    black_box(hasher.hash_encoded_with_salt(&[0; 16], &[1; 16]).unwrap());
+}
+
+fn group<'a, M: Measurement>(
+    c: &'a mut Criterion<M>,
+    prefix: &str,
+    name: &str,
+) -> BenchmarkGroup<'a, M> {
+    let mut group = c.benchmark_group(format!("{}{}", prefix, name));
+    // Even the smallest cases take milliseconds per iteration.
+    group.sample_size(10).sampling_mode(SamplingMode::Flat);
+    group
+}
+
+fn bench<M: Measurement>(group: &mut BenchmarkGroup<'_, M>, id: BenchmarkId, params: &Params) {
+    group.throughput(Throughput::Bytes(u64::from(params.m_cost) * 1024));
+    group.bench_with_input(id, params, |b, params| b.iter(|| hash(params)));
+}
+
+/// Memory sizes with 3 passes and 1 lane, as in `bench.c`.
+fn m_cost<M: Measurement>(c: &mut Criterion<M>, prefix: &str) {
+    let m_costs = if env_flag("ARGON2_SYS_BENCH_LARGE") {
+        &M_COSTS[..]
+    } else {
+        &M_COSTS[..DEFAULT_M_COSTS]
+    };
+    let mut group = group(c, prefix, "m_cost");
+    for &(ty, name) in &TYPES {
+        for &m_cost in m_costs {
+            let id = BenchmarkId::new(name, m_cost);
+            bench(&mut group, id, &params(ty, 3, m_cost, 1));
+        }
+    }
+    group.finish();
+}
+
+/// 1 to 10 passes over 16 MiB with 1 lane.
+fn t_cost<M: Measurement>(c: &mut Criterion<M>, prefix: &str) {
+    let mut group = group(c, prefix, "t_cost");
+    for &(ty, name) in &TYPES {
+        for t_cost in 1..=10 {
+            let id = BenchmarkId::new(name, t_cost);
+            bench(&mut group, id, &params(ty, t_cost, 1 << 14, 1));
+        }
+    }
+    group.finish();
+}
+
+/// 1 to 8 lanes and threads with 3 passes over 64 MiB.
+fn lanes<M: Measurement>(c: &mut Criterion<M>, prefix: &str) {
+    let mut group = group(c, prefix, "lanes");
+    for &(ty, name) in &TYPES {
+        for &lanes in &[1, 2, 4, 8] {
+            let id = BenchmarkId::new(format!("{}/{}", name, 1 << 16), lanes);
+            bench(&mut group, id, &params(ty, 3, 1 << 16, lanes));
+        }
+    }
+    group.finish();
+}
+
+/// 1 to 8 threads filling 8 lanes with 3 passes over 64 MiB. Unlike in
+/// `lanes`, the work is the same in every case, so only the cost and gain
+/// of threading is measured.
+#[cfg(not(any(argon2_sys_rust_backend, argon2_sys_no_threads)))]
+fn threads<M: Measurement>(c: &mut Criterion<M>, prefix: &str) {
+    use argon2_sys::{argon2_ctx, Argon2_Context, ARGON2_OK};
+
+    let mut group = group(c, prefix, "threads");
+    let params = params(Argon2_id, 3, 1 << 16, 8);
+    group.throughput(Throughput::Bytes(u64::from(params.m_cost) * 1024));
+    for &threads in &[1, 2, 4, 8] {
+        let id = BenchmarkId::new(format!("argon2id/{}", params.m_cost), threads);
+        group.bench_with_input(id, &threads, |b, &threads| {
+            let (pwd, salt, mut out) = ([0u8; 16], [1u8; 16], [0u8; 16]);
+            let inputs = common::Inputs {
+                pwd: &pwd,
+                salt: &salt,
+                ..Default::default()
+            };
+            b.iter(|| {
+                let mut context = Argon2_Context {
+                    threads,
+                    ..common::context(&params, &inputs, &mut out)
+                };
+                assert_eq!(unsafe { argon2_ctx(&mut context, params.ty) }, ARGON2_OK);
+                black_box(&out);
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Each implementation of the memory-filling core with 3 passes over
+/// 64 MiB and 1 lane: the C library as built (or, with the `dispatch`
+/// feature, `ref.c` and every `opt.c` build the CPU supports) and the
+/// pure-Rust port.
+fn backend<M: Measurement>(c: &mut Criterion<M>, prefix: &str) {
+    let mut group = group(c, prefix, "backend");
+    let params = params(Argon2_id, 3, 1 << 16, 1);
+
+    #[cfg(argon2_sys_dispatch)]
+    {
+        use argon2_sys::{force_backend, Backend};
+        for &backend in Backend::ALL.iter().filter(|b| b.is_supported()) {
+            force_backend(backend);
+            let id = BenchmarkId::new(format!("{:?}", backend).to_lowercase(), params.m_cost);
+            bench(&mut group, id, &params);
+        }
+        force_backend(Backend::detect());
+    }
+    #[cfg(not(any(argon2_sys_dispatch, argon2_sys_rust_backend)))]
+    {
+        bench(&mut group, BenchmarkId::new("c", params.m_cost), &params);
+    }
+
+    group.throughput(Throughput::Bytes(u64::from(params.m_cost) * 1024));
+    group.bench_with_input(
+        BenchmarkId::new("rust", params.m_cost),
+        &params,
+        |b, params| {
+            let mut out = [0u8; 16];
+            b.iter(|| argon2_sys::rust::hash(params, &[0; 16], &[1; 16], &[], &[], &mut out))
+        },
+    );
+    group.finish();
+}
+
+fn wall_time(c: &mut Criterion) {
+    m_cost(c, "");
+    t_cost(c, "");
+    lanes(c, "");
+    #[cfg(not(any(argon2_sys_rust_backend, argon2_sys_no_threads)))]
+    threads(c, "");
+    backend(c, "");
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod cycles {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::_rdtsc;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::_rdtsc;
+    use criterion::measurement::{Measurement, ValueFormatter};
+    use criterion::{Criterion, Throughput};
+
+    /// CPU cycles as counted by `rdtsc`, like in `bench.c`.
+    pub struct Cycles;
+
+    impl Measurement for Cycles {
+        type Intermediate = u64;
+        type Value = u64;
+
+        fn start(&self) -> u64 {
+            unsafe { _rdtsc() }
+        }
+
+        fn end(&self, start: u64) -> u64 {
+            unsafe { _rdtsc() }.wrapping_sub(start)
+        }
+
+        fn add(&self, a: &u64, b: &u64) -> u64 {
+            a + b
+        }
+
+        fn zero(&self) -> u64 {
+            0
+        }
+
+        fn to_f64(&self, value: &u64) -> f64 {
+            *value as f64
+        }
+
+        fn formatter(&self) -> &dyn ValueFormatter {
+            self
+        }
+    }
+
+    impl ValueFormatter for Cycles {
+        fn scale_values(&self, typical: f64, values: &mut [f64]) -> &'static str {
+            let (factor, unit) = if typical < 1e3 {
+                (1.0, "cycles")
+            } else if typical < 1e6 {
+                (1e-3, "Kcycles")
+            } else if typical < 1e9 {
+                (1e-6, "Mcycles")
+            } else {
+                (1e-9, "Gcycles")
+            };
+            for value in values {
+                *value *= factor;
+            }
+            unit
+        }
+
+        fn scale_throughputs(
+            &self,
+            _typical: f64,
+            throughput: &Throughput,
+            values: &mut [f64],
+        ) -> &'static str {
+            let (count, unit) = match *throughput {
+                Throughput::Bytes(bytes) | Throughput::BytesDecimal(bytes) => (bytes, "cpb"),
+                Throughput::Elements(elements) => (elements, "cycles/elem"),
+            };
+            for value in values {
+                *value /= count as f64;
+            }
+            unit
+        }
+
+        fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+            "cycles"
+        }
+    }
+
+    pub fn config() -> Criterion<Cycles> {
+        Criterion::default().with_measurement(Cycles)
+    }
+
+    pub fn cycles(c: &mut Criterion<Cycles>) {
+        if !super::env_flag("ARGON2_SYS_BENCH_CYCLES") {
+            return;
+        }
+        super::m_cost(c, "cycles/");
+        super::t_cost(c, "cycles/");
+        super::lanes(c, "cycles/");
+        #[cfg(not(any(argon2_sys_rust_backend, argon2_sys_no_threads)))]
+        super::threads(c, "cycles/");
+        super::backend(c, "cycles/");
+    }
+}
+
+criterion_group!(wall_time_benches, wall_time);
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+criterion_group! {
+    name = cycle_benches;
+    config = cycles::config();
+    targets = cycles::cycles
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+criterion_main!(wall_time_benches, cycle_benches);
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+criterion_main!(wall_time_benches);
@@ -1,4 +1,7 @@
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 #[cfg(feature = "simd")]
 const SIMD: bool = true;
@@ -6,33 +9,636 @@ const SIMD: bool = true;
 #[cfg(not(feature = "simd"))]
 const SIMD: bool = false;
 
+#[cfg(feature = "dispatch")]
+const DISPATCH: bool = true;
+
+#[cfg(not(feature = "dispatch"))]
+const DISPATCH: bool = false;
+
+#[cfg(feature = "rust-backend")]
+const RUST_BACKEND: bool = true;
+
+#[cfg(not(feature = "rust-backend"))]
+const RUST_BACKEND: bool = false;
+
+#[cfg(feature = "no-threads")]
+const NO_THREADS: bool = true;
+
+#[cfg(not(feature = "no-threads"))]
+const NO_THREADS: bool = false;
+
+#[cfg(feature = "system")]
+const SYSTEM: bool = true;
+
+#[cfg(not(feature = "system"))]
+const SYSTEM: bool = false;
+
+/// The sources compiled into every build, relative to the argon2 source
+/// tree.
 const FILES: &[&str] = &[
-    "argon2/src/argon2.c",
-    "argon2/src/core.c",
-    "argon2/src/blake2/blake2b.c",
-    "argon2/src/encoding.c",
-    "argon2/src/thread.c",
-    if SIMD {
-        "argon2/src/opt.c"
-    } else {
-        "argon2/src/ref.c"
-    },
+    "src/argon2.c",
+    "src/core.c",
+    "src/blake2/blake2b.c",
+    "src/encoding.c",
+    "src/thread.c",
+];
+
+/// The header, checked for `SYMBOLS` to tell an older release from the one
+/// the bindings were written against.
+const HEADER: &str = "include/argon2.h";
+
+/// Text that only appears in the 20171227 release and later, as (file,
+/// text). Older versions of opt.c have no AVX-512F code for `dispatch`.
+const MARKERS: &[(&str, &str)] = &[("src/blake2/blamka-round-opt.h", "__AVX512F__")];
+
+/// The x86 instruction sets `opt.c` can be compiled for, as (name, compiler
+/// flag), from oldest to newest.
+const SIMD_LEVELS: &[(&str, &str)] = &[
+    ("sse2", "-msse2"),
+    ("ssse3", "-mssse3"),
+    ("avx2", "-mavx2"),
+    ("avx512f", "-mavx512f"),
 ];
 
-const INCLUDE: &str = "argon2/include";
+const INCLUDE: &str = "include";
+
+/// Reports the layout of `argon2_context` and the values of the header
+/// constants to tests/layout.rs. Its object file is only linked into
+/// programs that call it.
+const LAYOUT: &str = "src/layout.c";
+
+/// The sanitizers `ARGON2_SYS_SANITIZE` may name.
+const SANITIZERS: &[&str] = &["address", "memory", "thread", "undefined"];
+
+/// The public functions declared in src/lib.rs, which any linked library
+/// must export.
+const SYMBOLS: &[&str] = &[
+    "argon2_type2string",
+    "argon2_ctx",
+    "argon2i_hash_encoded",
+    "argon2i_hash_raw",
+    "argon2d_hash_encoded",
+    "argon2d_hash_raw",
+    "argon2id_hash_encoded",
+    "argon2id_hash_raw",
+    "argon2_hash",
+    "argon2i_verify",
+    "argon2d_verify",
+    "argon2id_verify",
+    "argon2_verify",
+    "argon2d_ctx",
+    "argon2i_ctx",
+    "argon2id_ctx",
+    "argon2d_verify_ctx",
+    "argon2i_verify_ctx",
+    "argon2id_verify_ctx",
+    "argon2_verify_ctx",
+    "argon2_error_message",
+    "argon2_encodedlen",
+];
+
+/// The BLAKE2b functions declared in src/lib.rs. The reference build marks
+/// them hidden, so shared system libraries usually do not export them.
+const BLAKE2B_SYMBOLS: &[&str] = &[
+    "blake2b_init",
+    "blake2b_init_key",
+    "blake2b_init_param",
+    "blake2b_update",
+    "blake2b_final",
+    "blake2b",
+    "blake2b_long",
+];
 
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", LAYOUT);
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_TARGET_CPU");
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_USE_PKG_CONFIG");
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_STATIC");
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_ALLOW_VENDORED");
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_SANITIZE");
+    println!("cargo:rerun-if-env-changed=ARGON2_SYS_SRC_DIR");
+    println!("cargo:rustc-check-cfg=cfg(argon2_sys_blake2b)");
+    println!("cargo:rustc-check-cfg=cfg(argon2_sys_dispatch)");
+    println!("cargo:rustc-check-cfg=cfg(argon2_sys_no_threads)");
+    println!("cargo:rustc-check-cfg=cfg(argon2_sys_rust_backend)");
+    kats_dir();
+
+    // wasm32-unknown-unknown has no C library to build the sources against.
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    if RUST_BACKEND || (target_arch == "wasm32" && target_os == "unknown") {
+        // The safe API is implemented in Rust, and the raw bindings are
+        // left unlinked.
+        println!("cargo:rustc-cfg=argon2_sys_rust_backend");
+        if !threads() {
+            println!("cargo:rustc-cfg=argon2_sys_no_threads");
+        }
+        BuildInfo {
+            implementation: "rust",
+            simd_level: None,
+            threads: Some(false),
+            upstream_version: None,
+            upstream_commit: None,
+            pkg_config: false,
+        }
+        .emit();
+        return;
+    }
+
+    if SYSTEM || env_flag("ARGON2_SYS_USE_PKG_CONFIG") {
+        match link_system() {
+            Ok(()) => {
+                if !sanitizers().is_empty() {
+                    println!(
+                        "cargo:warning=ARGON2_SYS_SANITIZE has no effect on a system libargon2"
+                    );
+                }
+                return;
+            }
+            Err(message) if env_flag("ARGON2_SYS_ALLOW_VENDORED") => {
+                println!(
+                    "cargo:warning={}; falling back to the vendored sources",
+                    message
+                );
+            }
+            Err(message) => panic!(
+                "{}. Set ARGON2_SYS_ALLOW_VENDORED=1 to build the vendored sources instead.",
+                message
+            ),
+        }
+    }
+
+    build_vendored();
+}
+
+fn build_vendored() {
+    let dir = source_dir();
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let x86 = target_arch == "x86" || target_arch == "x86_64";
+    let dispatch = DISPATCH && x86;
+
+    let mut builder = new_build(&dir);
+    builder.files(
+        FILES
+            .iter()
+            .filter(|file| threads() || !file.ends_with("thread.c"))
+            .map(|file| dir.join(file)),
+    );
+    builder.file(LAYOUT);
+    if !threads() {
+        println!("cargo:rustc-cfg=argon2_sys_no_threads");
+    }
+
+    if dispatch {
+        // Each backend's `fill_segment` gets its own name; the one core.c
+        // calls is defined in src/dispatch.rs.
+        let mut backend = new_build(&dir);
+        backend
+            .file(dir.join("src/ref.c"))
+            .define("fill_segment", "argon2_sys_fill_segment_ref");
+        compile(&backend, "argon2_ref");
+        for (name, flag) in SIMD_LEVELS {
+            let symbol = format!("argon2_sys_fill_segment_{}", name);
+            let mut backend = new_build(&dir);
+            backend
+                .file(dir.join("src/opt.c"))
+                .define("fill_segment", symbol.as_str())
+                .flag(flag);
+            compile(&backend, &format!("argon2_{}", name));
+        }
+        println!("cargo:rustc-cfg=argon2_sys_dispatch");
+    } else if let Some(level) = simd_level() {
+        if !x86 {
+            // opt.c is written against SSE2 intrinsics. Upstream has no
+            // NEON or other non-x86 version of it.
+            panic!(
+                "SIMD level `{}` is not available for `{}`: opt.c only supports x86 and \
+                 x86_64, and has no NEON implementation. Disable the `simd` features and \
+                 unset ARGON2_SYS_TARGET_CPU to build ref.c instead.",
+                level,
+                env::var("TARGET").unwrap()
+            );
+        }
+        builder.file(dir.join("src/opt.c"));
+        if level == "native" {
+            builder.flag_if_supported("-march=native");
+        } else if let Some((_, flag)) = SIMD_LEVELS.iter().find(|(name, _)| *name == level) {
+            builder.flag(flag);
+        } else {
+            panic!(
+                "unknown SIMD level `{}` in ARGON2_SYS_TARGET_CPU, expected one of \
+                 native, sse2, ssse3, avx2, avx512f",
+                level
+            );
+        }
+    } else {
+        builder.file(dir.join("src/ref.c"));
+    }
+
+    compile(&builder, "argon2");
+    println!("cargo:rustc-cfg=argon2_sys_blake2b");
+
+    let level = if dispatch { None } else { simd_level() };
+    BuildInfo {
+        implementation: match level {
+            _ if dispatch => "dispatch",
+            Some(_) => "opt",
+            None => "ref",
+        },
+        simd_level: level.as_deref(),
+        threads: Some(threads()),
+        upstream_version: upstream_version(&dir),
+        upstream_commit: upstream_commit(&dir),
+        pkg_config: false,
+    }
+    .emit();
+}
+
+/// What `argon2_sys::build_info()` reports, passed to the crate as
+/// `ARGON2_SYS_BUILD_*` variables. `None` is passed as an empty string.
+struct BuildInfo<'a> {
+    /// `ref`, `opt`, `dispatch`, `rust`, or `unknown` for a system library.
+    implementation: &'a str,
+    simd_level: Option<&'a str>,
+    threads: Option<bool>,
+    upstream_version: Option<String>,
+    upstream_commit: Option<String>,
+    pkg_config: bool,
+}
+
+impl BuildInfo<'_> {
+    fn emit(&self) {
+        let threads = self.threads.map(|threads| threads.to_string());
+        let variables = [
+            ("IMPLEMENTATION", Some(self.implementation)),
+            ("SIMD_LEVEL", self.simd_level),
+            ("THREADS", threads.as_deref()),
+            ("UPSTREAM_VERSION", self.upstream_version.as_deref()),
+            ("UPSTREAM_COMMIT", self.upstream_commit.as_deref()),
+            (
+                "PKG_CONFIG",
+                Some(if self.pkg_config { "true" } else { "false" }),
+            ),
+        ];
+        for (name, value) in &variables {
+            println!(
+                "cargo:rustc-env=ARGON2_SYS_BUILD_{}={}",
+                name,
+                value.unwrap_or_default()
+            );
+        }
+        println!(
+            "cargo:rustc-env=ARGON2_SYS_BUILD_OPT_LEVEL={}",
+            env::var("OPT_LEVEL").unwrap()
+        );
+        println!(
+            "cargo:rustc-env=ARGON2_SYS_BUILD_DEBUG={}",
+            env::var("DEBUG").unwrap()
+        );
+    }
+}
+
+/// Returns the latest release named in the source tree's CHANGELOG.md,
+/// such as `20171227`.
+fn upstream_version(dir: &Path) -> Option<String> {
+    let changelog = fs::read_to_string(dir.join("CHANGELOG.md")).ok()?;
+    changelog
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|version| version.trim().to_owned())
+}
+
+/// Returns the commit checked out in the source tree, if it is a git
+/// checkout of its own rather than part of an enclosing repository.
+fn upstream_commit(dir: &Path) -> Option<String> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8(output.stdout).ok()?.trim().to_owned())
+    };
+    let toplevel = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?);
+    if toplevel.canonicalize().ok()? != dir.canonicalize().ok()? {
+        return None;
+    }
+    git(&["rev-parse", "HEAD"])
+}
+
+/// Returns the argon2 source tree to build: `ARGON2_SYS_SRC_DIR` if set,
+/// otherwise the `argon2` submodule.
+///
+/// Panics with instructions if the tree is missing files, which is what an
+/// uninitialized submodule looks like, or is from an older release.
+/// Tells `tests/kat.rs` where to embed the upstream test vectors from. They
+/// are not checked for here, since linking a system library or using the
+/// Rust port does not need the sources otherwise.
+fn kats_dir() {
+    let dir = match env::var_os("ARGON2_SYS_SRC_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("argon2"),
+    };
+    println!(
+        "cargo:rustc-env=ARGON2_SYS_KATS_DIR={}",
+        dir.join("kats").display()
+    );
+}
+
+fn source_dir() -> PathBuf {
+    let (dir, remedy) = match env::var_os("ARGON2_SYS_SRC_DIR") {
+        Some(dir) => (
+            PathBuf::from(dir),
+            "Point ARGON2_SYS_SRC_DIR at a checkout of \
+             https://github.com/P-H-C/phc-winner-argon2 from 20171227 or later.",
+        ),
+        None => (
+            PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("argon2"),
+            "Run `git submodule update --init` to check out the version this crate is \
+             tested with, or set ARGON2_SYS_SRC_DIR to another checkout of \
+             https://github.com/P-H-C/phc-winner-argon2.",
+        ),
+    };
+
+    let required = FILES
+        .iter()
+        .chain(&["src/ref.c", "src/opt.c", HEADER])
+        .chain(MARKERS.iter().map(|(file, _)| file));
+    for file in required {
+        if !dir.join(file).is_file() {
+            panic!(
+                "no argon2 sources in {}: {} is missing. {}",
+                dir.display(),
+                file,
+                remedy
+            );
+        }
+    }
+
+    let header = fs::read_to_string(dir.join(HEADER)).unwrap();
+    let missing = SYMBOLS.iter().find(|symbol| !header.contains(*symbol));
+    let outdated = MARKERS
+        .iter()
+        .any(|(file, text)| !fs::read_to_string(dir.join(file)).unwrap().contains(text));
+    if missing.is_some() || outdated {
+        panic!(
+            "the argon2 sources in {} are older than the 20171227 release{}. {}",
+            dir.display(),
+            missing.map_or(String::new(), |symbol| format!(
+                ": {} does not declare `{}`",
+                HEADER, symbol
+            )),
+            remedy
+        );
+    }
+    dir
+}
+
+/// Links against libargon2 as found by pkg-config, after checking that it
+/// exports every function the bindings declare.
+fn link_system() -> Result<(), String> {
+    let statik = env_flag("ARGON2_SYS_STATIC");
+    let library = pkg_config::Config::new()
+        .statik(statik)
+        .cargo_metadata(false)
+        .probe("libargon2")
+        .map_err(|error| format!("libargon2 was not found by pkg-config: {}", error))?;
+
+    let mut link_args = Vec::new();
+    for path in &library.link_paths {
+        link_args.push(format!("-L{}", path.display()));
+    }
+    for lib in &library.libs {
+        // Link the way the crate does below: libargon2 statically and the
+        // rest, libc included, dynamically. `-static` would also need a
+        // static libc.
+        if statik && lib == "argon2" {
+            link_args.push("-Wl,-Bstatic".to_string());
+            link_args.push(format!("-l{}", lib));
+            link_args.push("-Wl,-Bdynamic".to_string());
+        } else {
+            link_args.push(format!("-l{}", lib));
+        }
+    }
+
+    if !links("symbols", SYMBOLS, &link_args) {
+        return Err(format!(
+            "libargon2 {} does not export all of {}",
+            library.version,
+            SYMBOLS.join(", ")
+        ));
+    }
+    if links("blake2b", BLAKE2B_SYMBOLS, &link_args) {
+        println!("cargo:rustc-cfg=argon2_sys_blake2b");
+    }
+
+    // Against the library's own headers, which may differ from the
+    // vendored ones. Only tests/layout.rs needs it, so a failure here is not
+    // fatal to the build.
+    let layout = cc::Build::new()
+        .file(LAYOUT)
+        .includes(&library.include_paths)
+        .warnings(false)
+        .try_compile("argon2_sys_layout");
+    if let Err(error) = layout {
+        println!("cargo:warning=could not compile {}: {}", LAYOUT, error);
+    }
+
+    // The library is built with threads or not regardless of the feature,
+    // but it fills the lanes on the calling thread whenever the context
+    // asks for one thread, which the safe API then always does.
+    if !threads() {
+        println!("cargo:rustc-cfg=argon2_sys_no_threads");
+    }
+    BuildInfo {
+        implementation: "unknown",
+        simd_level: None,
+        threads: if threads() { None } else { Some(false) },
+        upstream_version: Some(library.version.clone()),
+        upstream_commit: None,
+        pkg_config: true,
+    }
+    .emit();
+
+    for path in &library.link_paths {
+        println!("cargo:rustc-link-search=native={}", path.display());
+    }
+    for lib in &library.libs {
+        if statik && lib == "argon2" {
+            println!("cargo:rustc-link-lib=static={}", lib);
+        } else {
+            println!("cargo:rustc-link-lib={}", lib);
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether a program referencing `symbols` links with `link_args`.
+fn links(name: &str, symbols: &[&str], link_args: &[String]) -> bool {
+    let compiler = cc::Build::new().get_compiler();
+    if compiler.is_like_msvc() {
+        // Assume the import library is complete rather than translating
+        // the link arguments for cl.exe.
+        return true;
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let source = out_dir.join(format!("check_{}.c", name));
+    let mut program = String::new();
+    for symbol in symbols {
+        program.push_str(&format!("extern void {}(void);\n", symbol));
+    }
+    program.push_str("int main(void) {\n    void (*volatile symbols[])(void) = {\n");
+    for symbol in symbols {
+        program.push_str(&format!("        {},\n", symbol));
+    }
+    program.push_str("    };\n    return symbols[0] == 0;\n}\n");
+    fs::write(&source, program).unwrap();
+
+    compiler
+        .to_command()
+        .arg(&source)
+        .arg("-o")
+        .arg(out_dir.join(format!("check_{}", name)))
+        .args(link_args)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Returns the instruction set to compile `opt.c` for, or `None` to use
+/// `ref.c`.
+///
+/// `ARGON2_SYS_TARGET_CPU` selects `opt.c` on its own, and takes
+/// precedence over the newest enabled `simd-*` feature. Plain `simd` means
+/// `native`.
+fn simd_level() -> Option<String> {
+    match env::var("ARGON2_SYS_TARGET_CPU") {
+        Ok(level) if !level.is_empty() => return Some(level),
+        _ => {}
+    }
+    if !SIMD {
+        return None;
+    }
+    let features = [
+        ("avx512f", cfg!(feature = "simd-avx512f")),
+        ("avx2", cfg!(feature = "simd-avx2")),
+        ("ssse3", cfg!(feature = "simd-ssse3")),
+        ("sse2", cfg!(feature = "simd-sse2")),
+    ];
+    let level = features
+        .iter()
+        .find(|(_, enabled)| *enabled)
+        .map_or("native", |(name, _)| *name);
+    if level == "native" && cross_compiling() {
+        panic!(
+            "the `simd` feature tunes opt.c for the build machine (`{}`), which is not the \
+             target (`{}`). Enable one of the `simd-*` features, or set \
+             ARGON2_SYS_TARGET_CPU to native if the target is known to run on this machine.",
+            env::var("HOST").unwrap(),
+            env::var("TARGET").unwrap()
+        );
+    }
+    Some(level.to_owned())
+}
+
+fn cross_compiling() -> bool {
+    env::var("HOST").unwrap() != env::var("TARGET").unwrap()
+}
+
+/// Returns whether to build `thread.c`: not with the `no-threads` feature,
+/// nor for WebAssembly or bare-metal targets, which lack the pthreads it is
+/// written against.
+fn threads() -> bool {
+    !NO_THREADS
+        && env::var("CARGO_CFG_TARGET_ARCH").unwrap() != "wasm32"
+        && env::var("CARGO_CFG_TARGET_OS").unwrap() != "none"
+}
+
+/// Compiles `builder` into `lib<name>.a`. Bare-metal targets rarely have a C
+/// toolchain set up, so a failure there points at `rust-backend` instead.
+fn compile(builder: &cc::Build, name: &str) {
+    if let Err(err) = builder.try_compile(name) {
+        if env::var("CARGO_CFG_TARGET_OS").unwrap() == "none" {
+            panic!(
+                "failed to compile the argon2 C sources for `{}`: {}\n\n\
+                 Bare-metal targets need a C cross compiler and a libc that provides \
+                 malloc and free, e.g. arm-none-eabi-gcc with newlib, set in CC_{}. \
+                 Or enable the `rust-backend` feature, which builds no C at all.",
+                env::var("TARGET").unwrap(),
+                err,
+                env::var("TARGET").unwrap().replace('-', "_")
+            );
+        }
+        panic!("failed to compile the argon2 C sources: {}", err);
+    }
+}
+
+/// Returns the sanitizers to compile the C sources with, from the
+/// comma-separated `ARGON2_SYS_SANITIZE`.
+fn sanitizers() -> Vec<String> {
+    let value = env::var("ARGON2_SYS_SANITIZE").unwrap_or_default();
+    let sanitizers: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect();
+    for name in &sanitizers {
+        if !SANITIZERS.contains(&name.as_str()) {
+            panic!(
+                "unknown sanitizer `{}` in ARGON2_SYS_SANITIZE, expected one of {}",
+                name,
+                SANITIZERS.join(", ")
+            );
+        }
+    }
+    sanitizers
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name).map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+fn new_build(dir: &Path) -> cc::Build {
     let mut builder = cc::Build::new();
     builder
-        .files(FILES)
-        .include(INCLUDE)
-        .flag_if_supported("-pthread")
+        .include(dir.join(INCLUDE))
         .flag_if_supported("-std=c89")
         .warnings(false)
         .extra_warnings(false);
 
-    if SIMD {
-        builder.flag_if_supported("-march=native");
+    if threads() {
+        // thread.c uses the Windows API rather than pthreads on Windows.
+        let family = env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+        if family.split(',').any(|family| family == "unix") {
+            builder.flag_if_supported("-pthread");
+        }
+    } else {
+        builder.define("ARGON2_NO_THREADS", None);
+    }
+
+    let sanitizers = sanitizers();
+    if !sanitizers.is_empty() {
+        // The runtimes are linked by rustc's matching `-Zsanitizer`, except
+        // for UBSan, which rustc has none of, so it traps instead.
+        builder
+            .flag(format!("-fsanitize={}", sanitizers.join(",")))
+            .flag("-fno-omit-frame-pointer")
+            .flag("-g");
+        if sanitizers.iter().any(|name| name == "undefined") {
+            builder.flag("-fsanitize-undefined-trap-on-error");
+        }
+        if sanitizers.iter().any(|name| name == "memory") {
+            builder.flag("-fsanitize-memory-track-origins");
+        }
     }
 
     let opt_level = env::var("OPT_LEVEL").unwrap();
@@ -41,5 +647,5 @@ fn main() {
         builder.flag_if_supported("-g");
     }
 
-    builder.compile("argon2");
+    builder
 }
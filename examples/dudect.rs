@@ -0,0 +1,139 @@
+//! dudect-style timing tests for the parts of the C library that are meant
+//! not to depend on secret data:
+//!
+//! - `argon2i_ctx`, whose memory accesses only depend on the public
+//!   parameters.
+//! - `argon2id_ctx`, of which only the first half-pass is data-independent.
+//!   The rest indexes memory by the password, so a difference there is
+//!   expected once the memory no longer fits in the cache.
+//! - `argon2_verify_ctx`, which compares the computed tag with the expected
+//!   one without returning early.
+//!
+//! Each test interleaves two classes of inputs at random and runs Welch's
+//! t-test on the timings. `|t|` above 5 means timing tells the classes apart.
+//!
+//! ```sh
+//! cargo run --release --example dudect
+//! cargo run --release --example dudect -- --continuous argon2i_ctx
+//! ```
+
+#[cfg(all(not(argon2_sys_rust_backend), not(target_arch = "wasm32")))]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+#[cfg(all(not(argon2_sys_rust_backend), not(target_arch = "wasm32")))]
+mod ct {
+    use crate::common;
+    use argon2_sys::{Argon2_Context, Argon2_id, Params, ARGON2_OK, ARGON2_VERIFY_MISMATCH};
+    use dudect_bencher::rand::Rng;
+    use dudect_bencher::{BenchRng, Class, CtRunner};
+    use std::os::raw::c_int;
+
+    /// Measurements per run of a test.
+    const SAMPLES: usize = 10_000;
+    const SALT: &[u8] = b"somesalt";
+    const PWD_LEN: usize = 32;
+    /// Long enough for the comparison in `argon2_verify_ctx` to stand out
+    /// from the hashing if it were to return at the first difference.
+    const VERIFY_TAG_LEN: usize = 4096;
+
+    /// 1 pass over 64 KiB in one lane.
+    fn context(pwd: &[u8], out: &mut [u8]) -> Argon2_Context {
+        let params = Params {
+            t_cost: 1,
+            m_cost: 64,
+            lanes: 1,
+            ..Params::default()
+        };
+        let inputs = common::Inputs {
+            pwd,
+            salt: SALT,
+            ..Default::default()
+        };
+        common::context(&params, &inputs, out)
+    }
+
+    fn hash(pwd: &[u8], tag_len: usize) -> Vec<u8> {
+        let mut out = vec![0; tag_len];
+        let mut ctx = context(pwd, &mut out);
+        assert_eq!(unsafe { argon2_sys::argon2id_ctx(&mut ctx) }, ARGON2_OK);
+        out
+    }
+
+    /// A fixed password in the left class, random ones in the right.
+    fn passwords(rng: &mut BenchRng) -> Vec<(Class, Vec<u8>)> {
+        (0..SAMPLES)
+            .map(|_| {
+                let mut pwd = vec![0; PWD_LEN];
+                if rng.gen::<bool>() {
+                    (Class::Left, pwd)
+                } else {
+                    rng.fill(&mut pwd[..]);
+                    (Class::Right, pwd)
+                }
+            })
+            .collect()
+    }
+
+    fn run(
+        runner: &mut CtRunner,
+        rng: &mut BenchRng,
+        f: unsafe extern "C" fn(*mut Argon2_Context) -> c_int,
+    ) {
+        for (class, pwd) in passwords(rng) {
+            runner.run_one(class, || {
+                let mut out = [0; 32];
+                let mut ctx = context(&pwd, &mut out);
+                unsafe { f(&mut ctx) }
+            });
+        }
+    }
+
+    pub fn argon2i_ctx(runner: &mut CtRunner, rng: &mut BenchRng) {
+        run(runner, rng, argon2_sys::argon2i_ctx);
+    }
+
+    pub fn argon2id_ctx(runner: &mut CtRunner, rng: &mut BenchRng) {
+        run(runner, rng, argon2_sys::argon2id_ctx);
+    }
+
+    /// The expected tag differs from the computed one in its first byte in
+    /// the left class and in its last byte in the right, so both fail
+    /// verification after the same work.
+    pub fn argon2_verify_ctx(runner: &mut CtRunner, rng: &mut BenchRng) {
+        let pwd = [0; PWD_LEN];
+        let tag = hash(&pwd, VERIFY_TAG_LEN);
+        for _ in 0..SAMPLES {
+            let mut expected = tag.clone();
+            let (class, i) = if rng.gen::<bool>() {
+                (Class::Left, 0)
+            } else {
+                (Class::Right, VERIFY_TAG_LEN - 1)
+            };
+            expected[i] ^= 1;
+            runner.run_one(class, || {
+                let mut out = vec![0; VERIFY_TAG_LEN];
+                let mut ctx = context(&pwd, &mut out);
+                let code = unsafe {
+                    argon2_sys::argon2_verify_ctx(
+                        &mut ctx,
+                        expected.as_ptr() as *const _,
+                        Argon2_id,
+                    )
+                };
+                assert_eq!(code, ARGON2_VERIFY_MISMATCH);
+            });
+        }
+    }
+}
+
+#[cfg(all(not(argon2_sys_rust_backend), not(target_arch = "wasm32")))]
+use ct::{argon2_verify_ctx, argon2i_ctx, argon2id_ctx};
+
+#[cfg(all(not(argon2_sys_rust_backend), not(target_arch = "wasm32")))]
+dudect_bencher::ctbench_main!(argon2i_ctx, argon2id_ctx, argon2_verify_ctx);
+
+#[cfg(any(argon2_sys_rust_backend, target_arch = "wasm32"))]
+fn main() {
+    eprintln!("dudect: the C library is not linked, skipping");
+}
@@ -0,0 +1,76 @@
+//! Hashes arbitrary inputs with `argon2_ctx` and with the pure-Rust port,
+//! which must agree on the tag or on the error code.
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use argon2_sys::{argon2_ctx, rust, Params, ARGON2_OK};
+use argon2_sys::{ARGON2_VERSION_10, ARGON2_VERSION_13};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+
+/// The costs are kept small enough for thousands of runs per second, but
+/// include the invalid values 0 and, for the type, 3.
+#[derive(Debug, Arbitrary)]
+struct Input {
+    ty: u8,
+    version: bool,
+    t_cost: u8,
+    m_cost: u16,
+    lanes: u8,
+    outlen: u16,
+    pwd: Vec<u8>,
+    salt: Vec<u8>,
+    secret: Vec<u8>,
+    ad: Vec<u8>,
+}
+
+impl Input {
+    fn params(&self) -> Params {
+        Params {
+            ty: u32::from(self.ty % 4),
+            version: if self.version {
+                ARGON2_VERSION_13
+            } else {
+                ARGON2_VERSION_10
+            },
+            t_cost: u32::from(self.t_cost % 5),
+            m_cost: u32::from(self.m_cost % 1024),
+            lanes: u32::from(self.lanes % 9),
+            hash_len: u32::from(self.outlen % 1025),
+        }
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let params = input.params();
+
+    let mut expected = vec![0u8; params.hash_len as usize];
+    let inputs = common::Inputs {
+        pwd: &input.pwd,
+        salt: &input.salt,
+        secret: &input.secret,
+        ad: &input.ad,
+    };
+    let mut context = common::context(&params, &inputs, &mut expected);
+    let c_code = unsafe { argon2_ctx(&mut context, params.ty) };
+
+    let mut actual = vec![0u8; params.hash_len as usize];
+    let rust_code = match rust::hash(
+        &params,
+        &input.pwd,
+        &input.salt,
+        &input.secret,
+        &input.ad,
+        &mut actual,
+    ) {
+        Ok(()) => ARGON2_OK,
+        Err(error) => error.code().unwrap(),
+    };
+
+    assert_eq!(rust_code, c_code, "{:?}", params);
+    if c_code == ARGON2_OK {
+        assert_eq!(actual, expected, "{:?}", params);
+    }
+});
@@ -0,0 +1,85 @@
+//! Feeds arbitrary encoded strings to `argon2_verify`, so that they go
+//! through `decode_string` in `encoding.c`, and checks the outcome against
+//! `PhcHash::parse` and the pure-Rust port.
+#![no_main]
+
+use argon2_sys::{argon2_verify, rust, PhcHash, ARGON2_OK, ARGON2_VERIFY_MISMATCH};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use std::ffi::CString;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    ty: u8,
+    encoded: Vec<u8>,
+    pwd: Vec<u8>,
+}
+
+/// The largest decimal accepted after an `=`. Anything larger but still a
+/// `u32` would be taken as a cost and could take seconds to hash, so such
+/// inputs are skipped; larger numbers still are not, since both decoders
+/// must reject them.
+const MAX_DECIMAL: u64 = 64;
+
+fn is_cheap(encoded: &[u8]) -> bool {
+    let mut rest = encoded;
+    while let Some(i) = rest.iter().position(|&b| b == b'=') {
+        rest = &rest[i + 1..];
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let value = rest[..digits].iter().fold(0u64, |value, &b| {
+            value.saturating_mul(10).saturating_add(u64::from(b - b'0'))
+        });
+        if value > MAX_DECIMAL && value <= u64::from(u32::MAX) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns what `argon2_verify` should return for a string that
+/// `PhcHash::parse` accepts.
+fn expected(phc: &PhcHash, pwd: &[u8]) -> i32 {
+    let mut out = vec![0u8; phc.hash.len()];
+    match rust::hash(&phc.params(), pwd, &phc.salt, &[], &[], &mut out) {
+        Ok(()) if out == phc.hash => ARGON2_OK,
+        Ok(()) => ARGON2_VERIFY_MISMATCH,
+        Err(error) => error.code().unwrap(),
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let ty = u32::from(input.ty % 4);
+    let encoded = match CString::new(input.encoded) {
+        Ok(encoded) => encoded,
+        Err(_) => return,
+    };
+    if !is_cheap(encoded.as_bytes()) {
+        return;
+    }
+
+    let code = unsafe {
+        argon2_verify(
+            encoded.as_ptr(),
+            input.pwd.as_ptr() as *const _,
+            input.pwd.len(),
+            ty,
+        )
+    };
+
+    let phc = std::str::from_utf8(encoded.as_bytes())
+        .ok()
+        .and_then(|encoded| PhcHash::parse(encoded).ok())
+        .filter(|phc| phc.ty == ty);
+    match phc {
+        Some(phc) => assert_eq!(code, expected(&phc, &input.pwd), "{:?}", phc),
+        // `decode_string` checks the parameters before looking for trailing
+        // characters, so a string the Rust decoder rejects may fail with any
+        // error, but must not verify.
+        None => assert!(
+            code != ARGON2_OK && code != ARGON2_VERIFY_MISMATCH,
+            "{:?} decoded as {}",
+            encoded,
+            code
+        ),
+    }
+});
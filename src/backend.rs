@@ -0,0 +1,72 @@
+//! The implementation behind the safe API: the C library, or the pure-Rust
+//! port with the `rust-backend` feature.
+
+use crate::context::Inputs;
+use crate::error::Result;
+use crate::params::Params;
+use crate::Argon2_ErrorCodes;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+
+/// Computes the raw hash of `inputs` into `out`.
+#[cfg(not(argon2_sys_rust_backend))]
+pub(crate) fn hash(params: &Params, inputs: Inputs<'_>, out: &mut [u8]) -> Result<()> {
+    let mut context = crate::context::new(params, inputs, out)?;
+    crate::error::check(unsafe { crate::argon2_ctx(&mut context, params.ty) })
+}
+
+#[cfg(argon2_sys_rust_backend)]
+pub(crate) fn hash(params: &Params, inputs: Inputs<'_>, out: &mut [u8]) -> Result<()> {
+    // The port fills the lanes one after another whatever was asked for,
+    // but still rejects what the C library would.
+    crate::context::threads(params, inputs.max_threads)?;
+    crate::rust::hash(
+        params,
+        inputs.pwd,
+        inputs.salt,
+        inputs.secret,
+        inputs.ad,
+        out,
+    )
+}
+
+/// Checks that the raw hash of `inputs` equals `expected`, returning
+/// `ARGON2_VERIFY_MISMATCH` if it does not.
+#[cfg(all(feature = "alloc", not(argon2_sys_rust_backend)))]
+pub(crate) fn verify(params: &Params, inputs: Inputs<'_>, expected: &[u8]) -> Result<()> {
+    let mut out = vec![0u8; expected.len()];
+    let mut context = crate::context::new(params, inputs, &mut out)?;
+    let code =
+        unsafe { crate::argon2_verify_ctx(&mut context, expected.as_ptr() as *const _, params.ty) };
+    crate::error::check(code)
+}
+
+#[cfg(argon2_sys_rust_backend)]
+pub(crate) fn verify(params: &Params, inputs: Inputs<'_>, expected: &[u8]) -> Result<()> {
+    let mut out = vec![0u8; expected.len()];
+    hash(params, inputs, &mut out)?;
+    // Like `argon2_compare`, look at every byte regardless of where the
+    // first difference is.
+    let diff = out
+        .iter()
+        .zip(expected)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b));
+    crate::kdf::wipe(&mut out);
+    if diff == 0 {
+        Ok(())
+    } else {
+        Err(crate::Error::Argon2(crate::ARGON2_VERIFY_MISMATCH))
+    }
+}
+
+/// Returns the message for an `ARGON2_*` error code.
+#[cfg(not(argon2_sys_rust_backend))]
+pub(crate) fn error_message(code: Argon2_ErrorCodes) -> &'static str {
+    let message = unsafe { core::ffi::CStr::from_ptr(crate::argon2_error_message(code)) };
+    message.to_str().unwrap_or("Unknown error code")
+}
+
+#[cfg(argon2_sys_rust_backend)]
+pub(crate) fn error_message(code: Argon2_ErrorCodes) -> &'static str {
+    crate::rust::error_message(code)
+}
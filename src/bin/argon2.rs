@@ -0,0 +1,275 @@
+//! A command-line tool with the flags and output of the reference `argon2`
+//! tool, reading the password from stdin:
+//!
+//! ```sh
+//! echo -n password | argon2 somesalt -id -t 3 -m 16 -p 4 -l 32 -e
+//! ```
+//!
+//! `argon2 verify <encoded>` instead checks the password against an encoded
+//! hash of any variant, printing `Verification ok` or exiting with 1.
+
+use argon2_sys::{
+    verify_any, Argon2_d, Argon2_i, Argon2_id, Argon2_type, Hasher, Params, PhcHash,
+    ARGON2_MAX_LANES, ARGON2_MAX_MEMORY, ARGON2_MAX_THREADS, ARGON2_MAX_TIME, ARGON2_MISSING_ARGS,
+    ARGON2_VERSION_10, ARGON2_VERSION_13, ARGON2_VERSION_NUMBER,
+};
+use std::env;
+use std::io::{self, Read};
+use std::process;
+use std::time::Instant;
+
+const T_COST_DEF: u32 = 3;
+const LOG_M_COST_DEF: u32 = 12;
+const LANES_DEF: u32 = 1;
+const OUTLEN_DEF: u32 = 32;
+const MAX_PASS_LEN: usize = 128;
+
+fn usage(cmd: &str) {
+    println!(
+        "Usage:  {} [-h] salt [-i|-d|-id] [-t iterations] \
+         [-m log2(memory in KiB) | -k memory in KiB] [-p parallelism] \
+         [-l hash length] [-e|-r] [-v (10|13)]",
+        cmd
+    );
+    println!("        {} verify encoded", cmd);
+    println!("\tPassword is read from stdin");
+    println!("Parameters:");
+    println!("\tsalt\t\tThe salt to use, at least 8 characters");
+    println!("\tencoded\t\tAn encoded hash to verify the password against");
+    println!("\t-i\t\tUse Argon2i (this is the default)");
+    println!("\t-d\t\tUse Argon2d instead of Argon2i");
+    println!("\t-id\t\tUse Argon2id instead of Argon2i");
+    println!(
+        "\t-t N\t\tSets the number of iterations to N (default = {})",
+        T_COST_DEF
+    );
+    println!(
+        "\t-m N\t\tSets the memory usage of 2^N KiB (default {})",
+        LOG_M_COST_DEF
+    );
+    println!(
+        "\t-k N\t\tSets the memory usage of N KiB (default {})",
+        1 << LOG_M_COST_DEF
+    );
+    println!(
+        "\t-p N\t\tSets parallelism to N threads (default {})",
+        LANES_DEF
+    );
+    println!(
+        "\t-l N\t\tSets hash output length to N bytes (default {})",
+        OUTLEN_DEF
+    );
+    println!("\t-e\t\tOutput only encoded hash");
+    println!("\t-r\t\tOutput only the raw bytes of the hash");
+    println!(
+        "\t-v (10|13)\tArgon2 version (defaults to the most recent version, currently {:x})",
+        ARGON2_VERSION_NUMBER
+    );
+    println!("\t-h\t\tPrint {} usage", cmd);
+}
+
+fn fatal(error: &str) -> ! {
+    eprintln!("Error: {}", error);
+    process::exit(1);
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[allow(non_upper_case_globals)]
+fn type_name(ty: Argon2_type) -> &'static str {
+    match ty {
+        Argon2_d => "Argon2d",
+        Argon2_i => "Argon2i",
+        _ => "Argon2id",
+    }
+}
+
+/// Reads the password from stdin, which like in `run.c` must be shorter
+/// than `MAX_PASS_LEN` bytes.
+fn read_password() -> Vec<u8> {
+    let mut pwd = Vec::with_capacity(MAX_PASS_LEN);
+    let read = io::stdin()
+        .lock()
+        .take(MAX_PASS_LEN as u64)
+        .read_to_end(&mut pwd);
+    match read {
+        Ok(0) | Err(_) => fatal("no password read"),
+        Ok(MAX_PASS_LEN) => {
+            fatal("Provided password longer than supported in command line utility")
+        }
+        Ok(_) => pwd,
+    }
+}
+
+/// Parses a number the way `strtoul` does for well-formed input, with
+/// anything else read as 0.
+fn number(arg: &str) -> u64 {
+    arg.parse().unwrap_or(0)
+}
+
+fn verify(encoded: &str) {
+    let pwd = read_password();
+    match verify_any(encoded, &pwd) {
+        Ok(()) => println!("Verification ok"),
+        Err(error) => fatal(&error.to_string()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let cmd = args.first().map_or("argon2", String::as_str);
+    if args.len() < 2 {
+        usage(cmd);
+        process::exit(ARGON2_MISSING_ARGS);
+    } else if args[1] == "-h" {
+        usage(cmd);
+        process::exit(1);
+    } else if args[1] == "verify" {
+        match &args[2..] {
+            [encoded] => verify(encoded),
+            [] => fatal("missing encoded hash"),
+            _ => fatal("unknown argument"),
+        }
+        return;
+    }
+
+    let pwd = read_password();
+    let salt = &args[1];
+
+    let mut params = Params {
+        ty: Argon2_i,
+        version: ARGON2_VERSION_NUMBER,
+        t_cost: T_COST_DEF,
+        m_cost: 1 << LOG_M_COST_DEF,
+        lanes: LANES_DEF,
+        hash_len: OUTLEN_DEF,
+    };
+    let mut types_specified = 0;
+    let mut m_cost_specified = false;
+    let mut encoded_only = false;
+    let mut raw_only = false;
+
+    let mut args = args[2..].iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" => {
+                usage(cmd);
+                process::exit(1);
+            }
+            "-m" => {
+                if m_cost_specified {
+                    fatal("-m or -k can only be used once");
+                }
+                m_cost_specified = true;
+                let input = number(args.next().unwrap_or_else(|| fatal("missing -m argument")));
+                if input == 0 || input > 32 {
+                    fatal("bad numeric input for -m");
+                }
+                let m_cost = (1u64 << input).min(0xFFFFFFFF);
+                if m_cost > ARGON2_MAX_MEMORY {
+                    fatal("m_cost overflow");
+                }
+                params.m_cost = m_cost as u32;
+            }
+            "-k" => {
+                if m_cost_specified {
+                    fatal("-m or -k can only be used once");
+                }
+                m_cost_specified = true;
+                let input = number(args.next().unwrap_or_else(|| fatal("missing -k argument")));
+                if input == 0 {
+                    fatal("bad numeric input for -k");
+                }
+                let m_cost = input.min(0xFFFFFFFF);
+                if m_cost > ARGON2_MAX_MEMORY {
+                    fatal("m_cost overflow");
+                }
+                params.m_cost = m_cost as u32;
+            }
+            "-t" => {
+                let input = number(args.next().unwrap_or_else(|| fatal("missing -t argument")));
+                if input == 0 || input > ARGON2_MAX_TIME {
+                    fatal("bad numeric input for -t");
+                }
+                params.t_cost = input as u32;
+            }
+            "-p" => {
+                let input = number(args.next().unwrap_or_else(|| fatal("missing -p argument")));
+                if input == 0 || input > ARGON2_MAX_THREADS || input > ARGON2_MAX_LANES {
+                    fatal("bad numeric input for -p");
+                }
+                params.lanes = input as u32;
+            }
+            "-l" => {
+                let input = args.next().unwrap_or_else(|| fatal("missing -l argument"));
+                params.hash_len = number(input) as u32;
+            }
+            "-i" => {
+                params.ty = Argon2_i;
+                types_specified += 1;
+            }
+            "-d" => {
+                params.ty = Argon2_d;
+                types_specified += 1;
+            }
+            "-id" => {
+                params.ty = Argon2_id;
+                types_specified += 1;
+            }
+            "-e" => encoded_only = true,
+            "-r" => raw_only = true,
+            "-v" => {
+                params.version = match args.next().map(String::as_str) {
+                    Some("10") => ARGON2_VERSION_10,
+                    Some("13") => ARGON2_VERSION_13,
+                    Some(_) => fatal("invalid Argon2 version"),
+                    None => fatal("missing -v argument"),
+                };
+            }
+            _ => fatal("unknown argument"),
+        }
+    }
+
+    if types_specified > 1 {
+        fatal("cannot specify multiple Argon2 types");
+    }
+    if encoded_only && raw_only {
+        fatal("cannot provide both -e and -r");
+    }
+
+    if !encoded_only && !raw_only {
+        println!("Type:\t\t{}", type_name(params.ty));
+        println!("Iterations:\t{}", params.t_cost);
+        println!("Memory:\t\t{} KiB", params.m_cost);
+        println!("Parallelism:\t{}", params.lanes);
+    }
+
+    let start = Instant::now();
+    let encoded = Hasher::new(params)
+        .hash_encoded_with_salt(&pwd, salt.as_bytes())
+        .unwrap_or_else(|error| fatal(&error.to_string()));
+    let elapsed = start.elapsed();
+    let hash = PhcHash::parse(&encoded)
+        .unwrap_or_else(|error| fatal(&error.to_string()))
+        .hash;
+
+    if encoded_only {
+        println!("{}", encoded);
+    }
+    if raw_only {
+        println!("{}", hex(&hash));
+    }
+    if encoded_only || raw_only {
+        return;
+    }
+
+    println!("Hash:\t\t{}", hex(&hash));
+    println!("Encoded:\t{}", encoded);
+    println!("{:2.3} seconds", elapsed.as_secs_f64());
+    if let Err(error) = verify_any(&encoded, &pwd) {
+        fatal(&error.to_string());
+    }
+    println!("Verification ok");
+}
@@ -0,0 +1,37 @@
+//! Prints known-answer test vectors like the reference `genkat` tool:
+//!
+//! ```sh
+//! genkat [d|i|id] [16|19]
+//! ```
+
+use argon2_sys::rust::kat;
+use argon2_sys::{Argon2_d, Argon2_i, Argon2_id, ARGON2_VERSION_10, ARGON2_VERSION_NUMBER};
+use std::env;
+use std::process;
+
+fn fatal(error: &str) -> ! {
+    eprintln!("Error: {}", error);
+    process::exit(1);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let ty = match args.next().as_deref().unwrap_or("i") {
+        "d" => Argon2_d,
+        "i" => Argon2_i,
+        "id" => Argon2_id,
+        _ => fatal("wrong Argon2 type"),
+    };
+    let version = match args.next() {
+        Some(version) => version.parse().unwrap_or(0),
+        None => ARGON2_VERSION_NUMBER,
+    };
+    if version != ARGON2_VERSION_10 && version != ARGON2_VERSION_NUMBER {
+        fatal("wrong Argon2 version number");
+    }
+
+    match kat::genkat(ty, version) {
+        Ok(output) => print!("{}", output),
+        Err(error) => fatal(&error.to_string()),
+    }
+}
@@ -0,0 +1,180 @@
+//! The BLAKE2b implementation bundled with the reference implementation,
+//! or its Rust port with the `rust-backend` feature.
+
+use crate::error::{Error, Result};
+use crate::{
+    ARGON2_OUTPUT_TOO_LONG, ARGON2_OUTPUT_TOO_SHORT, ARGON2_SECRET_TOO_LONG, BLAKE2B_KEYBYTES,
+    BLAKE2B_OUTBYTES,
+};
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A streaming BLAKE2b hasher with an output length of 1 to 64 bytes.
+pub struct Blake2b {
+    state: imp::State,
+}
+
+impl Blake2b {
+    /// Creates an unkeyed hasher producing `outlen` bytes.
+    pub fn new(outlen: usize) -> Result<Blake2b> {
+        Blake2b::with_key(outlen, &[])
+    }
+
+    /// Creates a hasher producing `outlen` bytes, keyed with up to 64
+    /// bytes of `key`. An empty key is the same as no key.
+    pub fn with_key(outlen: usize, key: &[u8]) -> Result<Blake2b> {
+        if outlen == 0 {
+            return Err(Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+        }
+        if outlen > BLAKE2B_OUTBYTES {
+            return Err(Error::Argon2(ARGON2_OUTPUT_TOO_LONG));
+        }
+        if key.len() > BLAKE2B_KEYBYTES {
+            return Err(Error::Argon2(ARGON2_SECRET_TOO_LONG));
+        }
+        Ok(Blake2b {
+            state: imp::init(outlen, key),
+        })
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Blake2b {
+        imp::update(&mut self.state, data);
+        self
+    }
+
+    /// Returns the digest.
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self) -> Vec<u8> {
+        let mut out = vec![0u8; imp::outlen(&self.state)];
+        self.finalize_into(&mut out).unwrap();
+        out
+    }
+
+    /// Writes the digest to `out`, which must be exactly as long as the
+    /// output length given to `new`.
+    pub fn finalize_into(mut self, out: &mut [u8]) -> Result<()> {
+        let outlen = imp::outlen(&self.state);
+        if out.len() < outlen {
+            return Err(Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+        }
+        if out.len() > outlen {
+            return Err(Error::Argon2(ARGON2_OUTPUT_TOO_LONG));
+        }
+        imp::finalize(&mut self.state, out);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Blake2b {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blake2b")
+            .field("outlen", &imp::outlen(&self.state))
+            .finish()
+    }
+}
+
+/// Fills `out` using the variable-length hash function H' from the Argon2
+/// specification.
+pub fn blake2b_long(out: &mut [u8], input: &[u8]) -> Result<()> {
+    if out.is_empty() {
+        return Err(Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+    }
+    if out.len() > u32::MAX as usize {
+        return Err(Error::Argon2(ARGON2_OUTPUT_TOO_LONG));
+    }
+    imp::long(out, input);
+    Ok(())
+}
+
+/// The bundled C implementation. The callers have checked the lengths,
+/// which is all that can fail.
+#[cfg(not(argon2_sys_rust_backend))]
+mod imp {
+    use crate::kdf::wipe;
+    use crate::{
+        blake2b_final, blake2b_init, blake2b_init_key, blake2b_long, blake2b_state, blake2b_update,
+    };
+    use core::mem::MaybeUninit;
+
+    pub struct State(blake2b_state);
+
+    impl Drop for State {
+        fn drop(&mut self) {
+            // Unfinalized states may still hold the key in their buffer.
+            wipe(&mut self.0.buf);
+        }
+    }
+
+    pub fn init(outlen: usize, key: &[u8]) -> State {
+        let mut state = MaybeUninit::<blake2b_state>::uninit();
+        let code = unsafe {
+            if key.is_empty() {
+                blake2b_init(state.as_mut_ptr(), outlen)
+            } else {
+                blake2b_init_key(
+                    state.as_mut_ptr(),
+                    outlen,
+                    key.as_ptr() as *const _,
+                    key.len(),
+                )
+            }
+        };
+        assert_eq!(code, 0);
+        State(unsafe { state.assume_init() })
+    }
+
+    pub fn outlen(state: &State) -> usize {
+        state.0.outlen as usize
+    }
+
+    pub fn update(state: &mut State, data: &[u8]) {
+        unsafe { blake2b_update(&mut state.0, data.as_ptr() as *const _, data.len()) };
+    }
+
+    pub fn finalize(state: &mut State, out: &mut [u8]) {
+        unsafe { blake2b_final(&mut state.0, out.as_mut_ptr() as *mut _, out.len()) };
+    }
+
+    pub fn long(out: &mut [u8], input: &[u8]) {
+        let code = unsafe {
+            blake2b_long(
+                out.as_mut_ptr() as *mut _,
+                out.len(),
+                input.as_ptr() as *const _,
+                input.len(),
+            )
+        };
+        assert_eq!(code, 0);
+    }
+}
+
+/// The Rust port, which wipes its buffer on drop by itself.
+#[cfg(argon2_sys_rust_backend)]
+mod imp {
+    use crate::rust::blake2b;
+
+    pub use blake2b::State;
+
+    pub fn init(outlen: usize, key: &[u8]) -> State {
+        State::new(outlen, key)
+    }
+
+    pub fn outlen(state: &State) -> usize {
+        state.outlen()
+    }
+
+    pub fn update(state: &mut State, data: &[u8]) {
+        state.update(data);
+    }
+
+    pub fn finalize(state: &mut State, out: &mut [u8]) {
+        state.finalize(out);
+    }
+
+    pub fn long(out: &mut [u8], input: &[u8]) {
+        blake2b::long(out, &[input]);
+    }
+}
@@ -0,0 +1,68 @@
+//! How the linked Argon2 library was built, as observed by `build.rs`.
+
+/// The implementation of the memory-filling core of Argon2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Implementation {
+    /// The portable reference implementation (`ref.c`).
+    Ref,
+    /// The SIMD implementation (`opt.c`), for the instruction set in
+    /// [`BuildInfo::simd_level`].
+    Opt,
+    /// `ref.c` and `opt.c` for each x86 instruction set, chosen at runtime.
+    /// See `backend()` for the one in use.
+    Dispatch,
+    /// The pure-Rust port in the `rust` module.
+    Rust,
+    /// A system library, which does not report how it was built.
+    Unknown,
+}
+
+/// How the linked Argon2 library was built. Returned by [`build_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BuildInfo {
+    pub implementation: Implementation,
+    /// The instruction set `opt.c` was compiled for (`native`, `sse2`,
+    /// `ssse3`, `avx2` or `avx512f`), if it is the only implementation.
+    pub simd_level: Option<&'static str>,
+    /// Whether lanes are filled by threads, or `None` for a system library.
+    pub threads: Option<bool>,
+    /// The latest upstream release in the C sources, such as `20171227`, or
+    /// the version pkg-config reports for a system library.
+    pub upstream_version: Option<&'static str>,
+    /// The upstream commit, if the C sources were built from a git checkout.
+    pub upstream_commit: Option<&'static str>,
+    /// Whether the library was found by pkg-config.
+    pub pkg_config: bool,
+    /// The `OPT_LEVEL` the C sources were compiled with.
+    pub opt_level: &'static str,
+    /// Whether the C sources were compiled with debug information.
+    pub debug: bool,
+}
+
+/// Returns how the linked Argon2 library was built.
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        implementation: match env!("ARGON2_SYS_BUILD_IMPLEMENTATION") {
+            "ref" => Implementation::Ref,
+            "opt" => Implementation::Opt,
+            "dispatch" => Implementation::Dispatch,
+            "rust" => Implementation::Rust,
+            _ => Implementation::Unknown,
+        },
+        simd_level: non_empty(env!("ARGON2_SYS_BUILD_SIMD_LEVEL")),
+        threads: non_empty(env!("ARGON2_SYS_BUILD_THREADS")).map(|threads| threads == "true"),
+        upstream_version: non_empty(env!("ARGON2_SYS_BUILD_UPSTREAM_VERSION")),
+        upstream_commit: non_empty(env!("ARGON2_SYS_BUILD_UPSTREAM_COMMIT")),
+        pkg_config: env!("ARGON2_SYS_BUILD_PKG_CONFIG") == "true",
+        opt_level: env!("ARGON2_SYS_BUILD_OPT_LEVEL"),
+        debug: env!("ARGON2_SYS_BUILD_DEBUG") == "true",
+    }
+}
+
+fn non_empty(value: &'static str) -> Option<&'static str> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
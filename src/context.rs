@@ -0,0 +1,76 @@
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::{
+    Argon2_Context, ARGON2_AD_TOO_LONG, ARGON2_DEFAULT_FLAGS, ARGON2_MAX_THREADS,
+    ARGON2_MIN_THREADS, ARGON2_OUTPUT_TOO_LONG, ARGON2_PWD_TOO_LONG, ARGON2_SALT_TOO_LONG,
+    ARGON2_SECRET_TOO_LONG,
+};
+use core::convert::TryFrom;
+
+/// The inputs of a single Argon2 invocation besides its parameters.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Inputs<'a> {
+    pub pwd: &'a [u8],
+    pub salt: &'a [u8],
+    pub secret: &'a [u8],
+    pub ad: &'a [u8],
+    /// The most threads the caller allows, if limited.
+    pub max_threads: Option<u32>,
+}
+
+/// Builds a context writing to `out`.
+///
+/// The context borrows `out` and `inputs` through raw pointers, so it must
+/// not outlive them. No clearing flags are set, so the C library never
+/// writes through the input pointers.
+#[cfg_attr(argon2_sys_rust_backend, allow(dead_code))]
+pub(crate) fn new(params: &Params, inputs: Inputs<'_>, out: &mut [u8]) -> Result<Argon2_Context> {
+    Ok(Argon2_Context {
+        out: out.as_mut_ptr(),
+        outlen: len(out, ARGON2_OUTPUT_TOO_LONG)?,
+        pwd: ptr(inputs.pwd),
+        pwdlen: len(inputs.pwd, ARGON2_PWD_TOO_LONG)?,
+        salt: ptr(inputs.salt),
+        saltlen: len(inputs.salt, ARGON2_SALT_TOO_LONG)?,
+        secret: ptr(inputs.secret),
+        secretlen: len(inputs.secret, ARGON2_SECRET_TOO_LONG)?,
+        ad: ptr(inputs.ad),
+        adlen: len(inputs.ad, ARGON2_AD_TOO_LONG)?,
+        t_cost: params.t_cost,
+        m_cost: params.m_cost,
+        lanes: params.lanes,
+        threads: threads(params, inputs.max_threads)?,
+        version: params.version,
+        allocate_cbk: None,
+        free_cbk: None,
+        flags: ARGON2_DEFAULT_FLAGS,
+    })
+}
+
+/// Returns the number of threads to fill the lanes with: one per lane, up
+/// to `max_threads` clamped to the range the C library accepts.
+///
+/// Without thread.c, the lanes are filled one after another, so allowing
+/// more than one thread is an error.
+pub(crate) fn threads(params: &Params, max_threads: Option<u32>) -> Result<u32> {
+    let max_threads =
+        max_threads.map(|max| max.clamp(ARGON2_MIN_THREADS as u32, ARGON2_MAX_THREADS as u32));
+    match max_threads {
+        Some(max) if max > 1 && cfg!(argon2_sys_no_threads) => Err(Error::ThreadsUnsupported),
+        _ if cfg!(argon2_sys_no_threads) => Ok(1),
+        Some(max) => Ok(params.lanes.min(max)),
+        None => Ok(params.lanes),
+    }
+}
+
+fn ptr(bytes: &[u8]) -> *mut u8 {
+    if bytes.is_empty() {
+        core::ptr::null_mut()
+    } else {
+        bytes.as_ptr() as *mut u8
+    }
+}
+
+fn len(bytes: &[u8], too_long: core::ffi::c_int) -> Result<u32> {
+    u32::try_from(bytes.len()).map_err(|_| Error::Argon2(too_long))
+}
@@ -0,0 +1,133 @@
+//! Runtime selection of the `fill_segment` implementation.
+//!
+//! With the `dispatch` feature on x86 and x86_64, `build.rs` compiles
+//! `ref.c` and `opt.c` once per instruction set, each with its own symbol
+//! name. The `fill_segment` function that `core.c` calls is defined here and
+//! forwards to the fastest implementation the CPU supports, detected on
+//! first use. Every backend produces identical output.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// An implementation of the memory-filling core of Argon2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Backend {
+    /// The portable reference implementation (`ref.c`).
+    Ref = 1,
+    /// `opt.c` compiled for SSE2.
+    Sse2,
+    /// `opt.c` compiled for SSSE3.
+    Ssse3,
+    /// `opt.c` compiled for AVX2.
+    Avx2,
+    /// `opt.c` compiled for AVX-512F.
+    Avx512f,
+}
+
+impl Backend {
+    /// All backends, from slowest to fastest.
+    pub const ALL: [Backend; 5] = [
+        Backend::Ref,
+        Backend::Sse2,
+        Backend::Ssse3,
+        Backend::Avx2,
+        Backend::Avx512f,
+    ];
+
+    /// Returns the fastest backend supported by the current CPU.
+    pub fn detect() -> Backend {
+        Backend::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|backend| backend.is_supported())
+            .unwrap_or(Backend::Ref)
+    }
+
+    /// Returns whether the current CPU can run this backend.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Backend::Ref => true,
+            Backend::Sse2 => is_x86_feature_detected!("sse2"),
+            Backend::Ssse3 => is_x86_feature_detected!("ssse3"),
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            Backend::Avx512f => is_x86_feature_detected!("avx512f"),
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Backend> {
+        Backend::ALL.iter().copied().find(|&b| b as u8 == value)
+    }
+}
+
+/// The selected backend, or 0 if none has been selected yet.
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+/// Returns the backend used by all subsequent hashing, detecting it first
+/// if none has been selected yet.
+pub fn backend() -> Backend {
+    match Backend::from_u8(SELECTED.load(Ordering::Relaxed)) {
+        Some(backend) => backend,
+        None => {
+            let backend = Backend::detect();
+            SELECTED.store(backend as u8, Ordering::Relaxed);
+            backend
+        }
+    }
+}
+
+/// Makes all subsequent hashing use `backend`, e.g. to test a slower
+/// backend on a machine that supports a faster one.
+///
+/// # Panics
+///
+/// Panics if the current CPU does not support `backend`.
+pub fn force_backend(backend: Backend) {
+    assert!(
+        backend.is_supported(),
+        "the {:?} backend is not supported by this CPU",
+        backend
+    );
+    SELECTED.store(backend as u8, Ordering::Relaxed);
+}
+
+/// `argon2_position_t` from `core.h`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct argon2_position_t {
+    pass: u32,
+    lane: u32,
+    slice: u8,
+    index: u32,
+}
+
+extern "C" {
+    fn argon2_sys_fill_segment_ref(instance: *const core::ffi::c_void, position: argon2_position_t);
+    fn argon2_sys_fill_segment_sse2(
+        instance: *const core::ffi::c_void,
+        position: argon2_position_t,
+    );
+    fn argon2_sys_fill_segment_ssse3(
+        instance: *const core::ffi::c_void,
+        position: argon2_position_t,
+    );
+    fn argon2_sys_fill_segment_avx2(
+        instance: *const core::ffi::c_void,
+        position: argon2_position_t,
+    );
+    fn argon2_sys_fill_segment_avx512f(
+        instance: *const core::ffi::c_void,
+        position: argon2_position_t,
+    );
+}
+
+/// Called by `core.c` for every segment.
+#[no_mangle]
+unsafe extern "C" fn fill_segment(instance: *const core::ffi::c_void, position: argon2_position_t) {
+    match backend() {
+        Backend::Ref => argon2_sys_fill_segment_ref(instance, position),
+        Backend::Sse2 => argon2_sys_fill_segment_sse2(instance, position),
+        Backend::Ssse3 => argon2_sys_fill_segment_ssse3(instance, position),
+        Backend::Avx2 => argon2_sys_fill_segment_avx2(instance, position),
+        Backend::Avx512f => argon2_sys_fill_segment_avx512f(instance, position),
+    }
+}
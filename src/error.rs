@@ -0,0 +1,63 @@
+use crate::backend;
+use crate::{Argon2_ErrorCodes, ARGON2_OK};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+/// Errors returned by the safe API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The C library (or the equivalent check in Rust) reported an error code.
+    Argon2(Argon2_ErrorCodes),
+    /// The encoded hash names an algorithm no verifier is registered for.
+    #[cfg(feature = "alloc")]
+    UnknownAlgorithm(String),
+    /// No random salt could be generated.
+    #[cfg(feature = "getrandom")]
+    Rng(getrandom::Error),
+    /// More than one thread was requested from a build without threads,
+    /// such as one with the `no-threads` feature.
+    ThreadsUnsupported,
+}
+
+impl Error {
+    /// Returns the `ARGON2_*` error code, if there is one.
+    pub fn code(&self) -> Option<Argon2_ErrorCodes> {
+        match self {
+            Error::Argon2(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Argon2(code) => f.write_str(backend::error_message(*code)),
+            #[cfg(feature = "alloc")]
+            Error::UnknownAlgorithm(identifier) => {
+                write!(f, "Unknown algorithm identifier `{}`", identifier)
+            }
+            #[cfg(feature = "getrandom")]
+            Error::Rng(error) => write!(f, "Salt generation failed: {}", error),
+            Error::ThreadsUnsupported => {
+                f.write_str("Threads are not supported by this build of argon2-sys")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Converts an `ARGON2_*` return code into a `Result`.
+pub(crate) fn check(code: core::ffi::c_int) -> Result<()> {
+    if code == ARGON2_OK {
+        Ok(())
+    } else {
+        Err(Error::Argon2(code))
+    }
+}
@@ -0,0 +1,139 @@
+//! Password hashing into encoded strings, with salts generated on demand.
+
+use crate::backend;
+use crate::context::Inputs;
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::phc::PhcHash;
+use crate::{ARGON2_MIN_SALT_LENGTH, ARGON2_SALT_TOO_SHORT};
+use alloc::string::{String, ToString};
+use alloc::vec;
+
+/// The salt length used unless configured otherwise, in bytes.
+pub const DEFAULT_SALT_LEN: usize = 16;
+
+/// A source of random salts.
+///
+/// The default is `OsRng`, with the `getrandom` feature. Other sources are
+/// needed on targets `getrandom` does not support, and make hashes
+/// deterministic in tests.
+pub trait SaltSource {
+    /// Fills `salt` with random bytes.
+    fn fill_salt(&mut self, salt: &mut [u8]) -> Result<()>;
+}
+
+/// The operating system's CSPRNG, as exposed by `getrandom`.
+#[cfg(feature = "getrandom")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRng;
+
+#[cfg(feature = "getrandom")]
+impl SaltSource for OsRng {
+    fn fill_salt(&mut self, salt: &mut [u8]) -> Result<()> {
+        getrandom::getrandom(salt).map_err(Error::Rng)
+    }
+}
+
+impl<F> SaltSource for F
+where
+    F: FnMut(&mut [u8]) -> Result<()>,
+{
+    fn fill_salt(&mut self, salt: &mut [u8]) -> Result<()> {
+        self(salt)
+    }
+}
+
+/// Hashes passwords into encoded strings with freshly generated salts, drawn
+/// from `R`.
+#[derive(Debug, Clone)]
+pub struct Hasher<R> {
+    params: Params,
+    salt_len: usize,
+    max_threads: Option<u32>,
+    rng: R,
+}
+
+#[cfg(feature = "getrandom")]
+impl Hasher<OsRng> {
+    /// Creates a hasher drawing its salts from [`OsRng`].
+    pub fn new(params: Params) -> Hasher<OsRng> {
+        Hasher::with_rng(params, OsRng)
+    }
+}
+
+impl<R: SaltSource> Hasher<R> {
+    /// Creates a hasher drawing its salts from `rng`.
+    pub fn with_rng(params: Params, rng: R) -> Hasher<R> {
+        Hasher {
+            params,
+            salt_len: DEFAULT_SALT_LEN,
+            max_threads: None,
+            rng,
+        }
+    }
+
+    /// Sets the length of generated salts in bytes.
+    ///
+    /// With a length below `ARGON2_MIN_SALT_LENGTH`, `hash_encoded` fails
+    /// with `ARGON2_SALT_TOO_SHORT`.
+    pub fn salt_len(mut self, salt_len: usize) -> Hasher<R> {
+        self.salt_len = salt_len;
+        self
+    }
+
+    /// Limits the number of threads that fill the lanes, which is one per
+    /// lane by default. The hash does not depend on it.
+    ///
+    /// `max_threads` is clamped to `ARGON2_MIN_THREADS..=ARGON2_MAX_THREADS`.
+    /// Builds without threads (the `no-threads` feature, or WebAssembly)
+    /// fail with `Error::ThreadsUnsupported` if it is more than one.
+    pub fn max_threads(mut self, max_threads: u32) -> Hasher<R> {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Hashes `password` with a newly generated salt.
+    pub fn hash_encoded(&mut self, password: &[u8]) -> Result<String> {
+        if self.salt_len < ARGON2_MIN_SALT_LENGTH as usize {
+            return Err(Error::Argon2(ARGON2_SALT_TOO_SHORT));
+        }
+        let mut salt = vec![0u8; self.salt_len];
+        self.rng.fill_salt(&mut salt)?;
+        self.hash_encoded_with_salt(password, &salt)
+    }
+
+    /// Hashes `password` with the given salt.
+    pub fn hash_encoded_with_salt(&self, password: &[u8], salt: &[u8]) -> Result<String> {
+        let inputs = Inputs {
+            pwd: password,
+            salt,
+            max_threads: self.max_threads,
+            ..Inputs::default()
+        };
+        let mut hash = vec![0u8; self.params.hash_len as usize];
+        backend::hash(&self.params, inputs, &mut hash)?;
+
+        let phc = PhcHash {
+            ty: self.params.ty,
+            version: self.params.version,
+            explicit_version: true,
+            m_cost: self.params.m_cost,
+            t_cost: self.params.t_cost,
+            lanes: self.params.lanes,
+            salt: salt.to_vec(),
+            hash,
+        };
+        Ok(phc.to_string())
+    }
+}
+
+/// Hashes `password` with a salt of `DEFAULT_SALT_LEN` bytes from the
+/// operating system's CSPRNG.
+#[cfg(feature = "getrandom")]
+pub fn hash_encoded(password: &[u8], params: &Params) -> Result<String> {
+    Hasher::new(*params).hash_encoded(password)
+}
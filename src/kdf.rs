@@ -0,0 +1,147 @@
+//! Argon2 as a key derivation function.
+
+use crate::backend;
+use crate::context::Inputs;
+#[cfg(feature = "alloc")]
+use crate::error::Error;
+use crate::error::Result;
+use crate::params::Params;
+#[cfg(feature = "alloc")]
+use crate::{
+    ARGON2_MAX_OUTLEN, ARGON2_MIN_OUTLEN, ARGON2_OUTPUT_TOO_LONG, ARGON2_OUTPUT_TOO_SHORT,
+};
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// A fixed-size key that is wiped from memory when dropped.
+pub struct SecretArray<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretArray<N> {
+    pub fn expose(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Drop for SecretArray<N> {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl<const N: usize> fmt::Debug for SecretArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretArray<{}>(..)", N)
+    }
+}
+
+/// A variable-size key that is wiped from memory when dropped.
+#[cfg(feature = "alloc")]
+pub struct SecretVec(Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl SecretVec {
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for SecretVec {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for SecretVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretVec[{}](..)", self.0.len())
+    }
+}
+
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        unsafe { core::ptr::write_volatile(byte, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Derives an `N`-byte key from `password` and `salt`.
+///
+/// `params.hash_len` is ignored in favor of `N`, which must lie between
+/// `ARGON2_MIN_OUTLEN` and `ARGON2_MAX_OUTLEN`.
+pub fn derive_key<const N: usize>(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+) -> Result<SecretArray<N>> {
+    let mut key = SecretArray([0u8; N]);
+    let inputs = Inputs {
+        pwd: password,
+        salt,
+        ..Inputs::default()
+    };
+    backend::hash(params, inputs, &mut key.0)?;
+    Ok(key)
+}
+
+/// Derives one subkey per `(label, length)` pair, e.g. an encryption key,
+/// a MAC key and a key-check value, from a single Argon2 invocation.
+///
+/// The labels and lengths are bound into the associated data, so changing
+/// any of them yields unrelated keys. The output is then split in order.
+///
+/// The lengths must add up to between `ARGON2_MIN_OUTLEN` and
+/// `ARGON2_MAX_OUTLEN`; otherwise this fails with `ARGON2_OUTPUT_TOO_SHORT`
+/// or `ARGON2_OUTPUT_TOO_LONG` before hashing. Everything comes from one
+/// Argon2 output, so this does not expand keys beyond that limit: derive
+/// further keys with a KDF such as HKDF instead.
+#[cfg(feature = "alloc")]
+pub fn derive_subkeys(
+    password: &[u8],
+    salt: &[u8],
+    params: &Params,
+    subkeys: &[(&str, usize)],
+) -> Result<Vec<SecretVec>> {
+    let total = subkeys
+        .iter()
+        .try_fold(0usize, |total, (_, len)| total.checked_add(*len))
+        .ok_or(Error::Argon2(ARGON2_OUTPUT_TOO_LONG))?;
+    if (total as u64) < ARGON2_MIN_OUTLEN {
+        return Err(Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+    }
+    if total as u64 > ARGON2_MAX_OUTLEN {
+        return Err(Error::Argon2(ARGON2_OUTPUT_TOO_LONG));
+    }
+
+    let mut ad = b"argon2-sys subkeys".to_vec();
+    for (label, len) in subkeys {
+        ad.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        ad.extend_from_slice(label.as_bytes());
+        ad.extend_from_slice(&(*len as u64).to_le_bytes());
+    }
+
+    let mut output = SecretVec(vec![0u8; total]);
+    let inputs = Inputs {
+        pwd: password,
+        salt,
+        ad: &ad,
+        ..Inputs::default()
+    };
+    backend::hash(params, inputs, &mut output.0)?;
+
+    let mut rest = output.expose();
+    let keys = subkeys
+        .iter()
+        .map(|(_, len)| {
+            let (key, tail) = rest.split_at(*len);
+            rest = tail;
+            SecretVec(key.to_vec())
+        })
+        .collect();
+    Ok(keys)
+}
@@ -0,0 +1,142 @@
+/*
+ * Reports the layout of argon2_context and the values of the constants in
+ * argon2.h as the C compiler sees them, so that tests/layout.rs can compare
+ * them with the declarations in src/lib.rs. Nothing here is linked into a
+ * program that does not call these functions.
+ */
+
+#include <stddef.h>
+
+#include "argon2.h"
+
+struct argon2_sys_size {
+    const char *name;
+    size_t value;
+};
+
+struct argon2_sys_signed {
+    const char *name;
+    long value;
+};
+
+struct argon2_sys_unsigned {
+    const char *name;
+    unsigned long value;
+};
+
+struct argon2_sys_context_align {
+    char c;
+    argon2_context context;
+};
+
+#define FIELD(field) {#field, offsetof(argon2_context, field)}
+#define VALUE(constant) {#constant, constant}
+
+static const struct argon2_sys_size layout[] = {
+    {"sizeof(argon2_context)", sizeof(argon2_context)},
+    {"alignof(argon2_context)", offsetof(struct argon2_sys_context_align, context)},
+    {"sizeof(argon2_type)", sizeof(argon2_type)},
+    {"sizeof(argon2_version)", sizeof(argon2_version)},
+    {"sizeof(argon2_error_codes)", sizeof(argon2_error_codes)},
+    FIELD(out),
+    FIELD(outlen),
+    FIELD(pwd),
+    FIELD(pwdlen),
+    FIELD(salt),
+    FIELD(saltlen),
+    FIELD(secret),
+    FIELD(secretlen),
+    FIELD(ad),
+    FIELD(adlen),
+    FIELD(t_cost),
+    FIELD(m_cost),
+    FIELD(lanes),
+    FIELD(threads),
+    FIELD(version),
+    FIELD(allocate_cbk),
+    FIELD(free_cbk),
+    FIELD(flags),
+    {NULL, 0}
+};
+
+static const struct argon2_sys_signed signed_constants[] = {
+    VALUE(Argon2_d),
+    VALUE(Argon2_i),
+    VALUE(Argon2_id),
+    VALUE(ARGON2_VERSION_10),
+    VALUE(ARGON2_VERSION_13),
+    VALUE(ARGON2_VERSION_NUMBER),
+    VALUE(ARGON2_OK),
+    VALUE(ARGON2_OUTPUT_PTR_NULL),
+    VALUE(ARGON2_OUTPUT_TOO_SHORT),
+    VALUE(ARGON2_OUTPUT_TOO_LONG),
+    VALUE(ARGON2_PWD_TOO_SHORT),
+    VALUE(ARGON2_PWD_TOO_LONG),
+    VALUE(ARGON2_SALT_TOO_SHORT),
+    VALUE(ARGON2_SALT_TOO_LONG),
+    VALUE(ARGON2_AD_TOO_SHORT),
+    VALUE(ARGON2_AD_TOO_LONG),
+    VALUE(ARGON2_SECRET_TOO_SHORT),
+    VALUE(ARGON2_SECRET_TOO_LONG),
+    VALUE(ARGON2_TIME_TOO_SMALL),
+    VALUE(ARGON2_TIME_TOO_LARGE),
+    VALUE(ARGON2_MEMORY_TOO_LITTLE),
+    VALUE(ARGON2_MEMORY_TOO_MUCH),
+    VALUE(ARGON2_LANES_TOO_FEW),
+    VALUE(ARGON2_LANES_TOO_MANY),
+    VALUE(ARGON2_PWD_PTR_MISMATCH),
+    VALUE(ARGON2_SALT_PTR_MISMATCH),
+    VALUE(ARGON2_SECRET_PTR_MISMATCH),
+    VALUE(ARGON2_AD_PTR_MISMATCH),
+    VALUE(ARGON2_MEMORY_ALLOCATION_ERROR),
+    VALUE(ARGON2_FREE_MEMORY_CBK_NULL),
+    VALUE(ARGON2_ALLOCATE_MEMORY_CBK_NULL),
+    VALUE(ARGON2_INCORRECT_PARAMETER),
+    VALUE(ARGON2_INCORRECT_TYPE),
+    VALUE(ARGON2_OUT_PTR_MISMATCH),
+    VALUE(ARGON2_THREADS_TOO_FEW),
+    VALUE(ARGON2_THREADS_TOO_MANY),
+    VALUE(ARGON2_MISSING_ARGS),
+    VALUE(ARGON2_ENCODING_FAIL),
+    VALUE(ARGON2_DECODING_FAIL),
+    VALUE(ARGON2_THREAD_FAIL),
+    VALUE(ARGON2_DECODING_LENGTH_FAIL),
+    VALUE(ARGON2_VERIFY_MISMATCH),
+    {NULL, 0}
+};
+
+static const struct argon2_sys_unsigned unsigned_constants[] = {
+    VALUE(ARGON2_MIN_LANES),
+    VALUE(ARGON2_MAX_LANES),
+    VALUE(ARGON2_MIN_THREADS),
+    VALUE(ARGON2_MAX_THREADS),
+    VALUE(ARGON2_SYNC_POINTS),
+    VALUE(ARGON2_MIN_OUTLEN),
+    VALUE(ARGON2_MAX_OUTLEN),
+    VALUE(ARGON2_MIN_MEMORY),
+    VALUE(ARGON2_MAX_MEMORY),
+    VALUE(ARGON2_MIN_TIME),
+    VALUE(ARGON2_MAX_TIME),
+    VALUE(ARGON2_MIN_PWD_LENGTH),
+    VALUE(ARGON2_MAX_PWD_LENGTH),
+    VALUE(ARGON2_MIN_AD_LENGTH),
+    VALUE(ARGON2_MAX_AD_LENGTH),
+    VALUE(ARGON2_MIN_SALT_LENGTH),
+    VALUE(ARGON2_MAX_SALT_LENGTH),
+    VALUE(ARGON2_MIN_SECRET),
+    VALUE(ARGON2_MAX_SECRET),
+    VALUE(ARGON2_DEFAULT_FLAGS),
+    VALUE(ARGON2_FLAG_CLEAR_PASSWORD),
+    VALUE(ARGON2_FLAG_CLEAR_SECRET),
+    {NULL, 0}
+};
+
+const struct argon2_sys_size *argon2_sys_layout(void) { return layout; }
+
+const struct argon2_sys_signed *argon2_sys_signed_constants(void) {
+    return signed_constants;
+}
+
+const struct argon2_sys_unsigned *argon2_sys_unsigned_constants(void) {
+    return unsigned_constants;
+}
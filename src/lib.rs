@@ -1,6 +1,51 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+// The raw bindings and the parts of the safe API that write into
+// caller-provided buffers only need `core`. The rest returns `String`s and
+// `Vec`s, so it needs `alloc`.
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod backend;
+#[cfg(any(argon2_sys_blake2b, argon2_sys_rust_backend))]
+pub mod blake2b;
+mod build_info;
+mod context;
+#[cfg(argon2_sys_dispatch)]
+mod dispatch;
+mod error;
+#[cfg(feature = "alloc")]
+mod hash;
+mod kdf;
+mod params;
+#[cfg(feature = "alloc")]
+pub mod phc;
+#[cfg(feature = "alloc")]
+pub mod rust;
+#[cfg(feature = "alloc")]
+mod verify;
+
+#[cfg(any(argon2_sys_blake2b, argon2_sys_rust_backend))]
+pub use blake2b::Blake2b;
+pub use build_info::{build_info, BuildInfo, Implementation};
+#[cfg(argon2_sys_dispatch)]
+pub use dispatch::{backend, force_backend, Backend};
+pub use error::{Error, Result};
+#[cfg(all(feature = "alloc", feature = "getrandom"))]
+pub use hash::{hash_encoded, OsRng};
+#[cfg(feature = "alloc")]
+pub use hash::{Hasher, SaltSource, DEFAULT_SALT_LEN};
+pub use kdf::{derive_key, SecretArray};
+#[cfg(feature = "alloc")]
+pub use kdf::{derive_subkeys, SecretVec};
+pub use params::Params;
+#[cfg(feature = "alloc")]
+pub use phc::PhcHash;
+#[cfg(feature = "alloc")]
+pub use verify::{verify_any, verify_phc, Hook, Verifier};
+
 /// Minimum number of lanes (degree of parallelism)
 pub const ARGON2_MIN_LANES: u64 = 1;
 /// Maximum number of lanes (degree of parallelism)
@@ -17,12 +62,12 @@ pub const ARGON2_SYNC_POINTS: u64 = 4;
 /// Minimum digest size in bytes
 pub const ARGON2_MIN_OUTLEN: u64 = 4;
 /// Maximum digest size in bytes
-pub const ARGON2_MAX_OUTLEN: u64 = 0xFFFFFF;
+pub const ARGON2_MAX_OUTLEN: u64 = 0xFFFFFFFF;
 
 /// Minimum number of memory blocks (each of BLOCK_SIZE bytes)
 pub const ARGON2_MIN_MEMORY: u64 = 2 * ARGON2_SYNC_POINTS;
 
-const ADDRESSING_SPACE: u64 = (std::mem::size_of::<usize>() * 8) as u64;
+const ADDRESSING_SPACE: u64 = (core::mem::size_of::<usize>() * 8) as u64;
 
 const fn min(a: u64, b: u64) -> u64 {
     if a < b {
@@ -67,7 +112,7 @@ pub const ARGON2_DEFAULT_FLAGS: u32 = 0;
 pub const ARGON2_FLAG_CLEAR_PASSWORD: u32 = 1 << 0;
 pub const ARGON2_FLAG_CLEAR_SECRET: u32 = 1 << 1;
 
-pub type Argon2_ErrorCodes = libc::c_int;
+pub type Argon2_ErrorCodes = core::ffi::c_int;
 
 pub const ARGON2_OK: Argon2_ErrorCodes = 0;
 pub const ARGON2_OUTPUT_PTR_NULL: Argon2_ErrorCodes = -1;
@@ -106,12 +151,12 @@ pub const ARGON2_THREAD_FAIL: Argon2_ErrorCodes = -33;
 pub const ARGON2_DECODING_LENGTH_FAIL: Argon2_ErrorCodes = -34;
 pub const ARGON2_VERIFY_MISMATCH: Argon2_ErrorCodes = -35;
 
-pub type allocate_fptr = ::std::option::Option<
-    unsafe extern "C" fn(memory: *mut *mut u8, bytes_to_allocate: libc::size_t) -> libc::c_int,
+pub type allocate_fptr = ::core::option::Option<
+    unsafe extern "C" fn(memory: *mut *mut u8, bytes_to_allocate: usize) -> core::ffi::c_int,
 >;
 
 pub type deallocate_fptr =
-    ::std::option::Option<unsafe extern "C" fn(memory: *mut u8, bytes_to_allocate: libc::size_t)>;
+    ::core::option::Option<unsafe extern "C" fn(memory: *mut u8, bytes_to_allocate: usize)>;
 
 /// *****
 ///
@@ -165,14 +210,14 @@ pub struct Argon2_Context {
 
 pub type argon2_context = Argon2_Context;
 
-pub type Argon2_type = libc::c_uint;
+pub type Argon2_type = core::ffi::c_uint;
 pub use Argon2_type as argon2_type;
 
 pub const Argon2_d: Argon2_type = 0;
 pub const Argon2_i: Argon2_type = 1;
 pub const Argon2_id: Argon2_type = 2;
 
-pub type Argon2_version = libc::c_uint;
+pub type Argon2_version = core::ffi::c_uint;
 pub use Argon2_version as argon2_version;
 
 pub const ARGON2_VERSION_10: Argon2_version = 0x10;
@@ -187,14 +232,17 @@ extern "C" {
     /// @param uppercase Whether the string should have the first letter uppercase
     ///
     /// @return NULL if invalid type, otherwise the string representation.
-    pub fn argon2_type2string(ty: argon2_type, uppercase: libc::c_int) -> *const libc::c_char;
+    pub fn argon2_type2string(
+        ty: argon2_type,
+        uppercase: core::ffi::c_int,
+    ) -> *const core::ffi::c_char;
 
     /// Function that performs memory-hard hashing with certain degree of parallelism
     ///
     /// @param  context  Pointer to the Argon2 internal structure
     ///
     /// @return Error code if smth is wrong, ARGON2_OK otherwise
-    pub fn argon2_ctx(context: *mut argon2_context, ty: argon2_type) -> libc::c_int;
+    pub fn argon2_ctx(context: *mut argon2_context, ty: argon2_type) -> core::ffi::c_int;
 
     /// Hashes a password with Argon2i, producing an encoded hash
     ///
@@ -225,14 +273,14 @@ extern "C" {
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hashlen: libc::size_t,
-        encoded: *mut libc::c_char,
-        encodedlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hashlen: usize,
+        encoded: *mut core::ffi::c_char,
+        encodedlen: usize,
+    ) -> core::ffi::c_int;
 
     /// Hashes a password with Argon2i, producing a raw hash at @hash
     ///
@@ -261,79 +309,79 @@ extern "C" {
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hash: *mut libc::c_void,
-        hashlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hash: *mut core::ffi::c_void,
+        hashlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2d_hash_encoded(
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hashlen: libc::size_t,
-        encoded: *mut libc::c_char,
-        encodedlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hashlen: usize,
+        encoded: *mut core::ffi::c_char,
+        encodedlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2d_hash_raw(
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hash: *mut libc::c_void,
-        hashlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hash: *mut core::ffi::c_void,
+        hashlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2id_hash_encoded(
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hashlen: libc::size_t,
-        encoded: *mut libc::c_char,
-        encodedlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hashlen: usize,
+        encoded: *mut core::ffi::c_char,
+        encodedlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2id_hash_raw(
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hash: *mut libc::c_void,
-        hashlen: libc::size_t,
-    ) -> libc::c_int;
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hash: *mut core::ffi::c_void,
+        hashlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2_hash(
         t_cost: u32,
         m_cost: u32,
         parallelism: u32,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-        salt: *const libc::c_void,
-        saltlen: libc::size_t,
-        hash: *mut libc::c_void,
-        hashlen: libc::size_t,
-        encoded: *mut libc::c_char,
-        encodedlen: libc::size_t,
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+        salt: *const core::ffi::c_void,
+        saltlen: usize,
+        hash: *mut core::ffi::c_void,
+        hashlen: usize,
+        encoded: *mut core::ffi::c_char,
+        encodedlen: usize,
         ty: argon2_type,
         version: u32,
-    ) -> libc::c_int;
+    ) -> core::ffi::c_int;
 
     /// Verifies a password against an encoded string
     ///
@@ -345,29 +393,29 @@ extern "C" {
     ///
     /// @pre   Returns ARGON2_OK if successful
     pub fn argon2i_verify(
-        encoded: *const libc::c_char,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-    ) -> libc::c_int;
+        encoded: *const core::ffi::c_char,
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2d_verify(
-        encoded: *const libc::c_char,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-    ) -> libc::c_int;
+        encoded: *const core::ffi::c_char,
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2id_verify(
-        encoded: *const libc::c_char,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
-    ) -> libc::c_int;
+        encoded: *const core::ffi::c_char,
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
+    ) -> core::ffi::c_int;
 
     pub fn argon2_verify(
-        encoded: *const libc::c_char,
-        pwd: *const libc::c_void,
-        pwdlen: libc::size_t,
+        encoded: *const core::ffi::c_char,
+        pwd: *const core::ffi::c_void,
+        pwdlen: usize,
         ty: argon2_type,
-    ) -> libc::c_int;
+    ) -> core::ffi::c_int;
 
     ///  Argon2d: Version of Argon2 that picks memory blocks depending
     ///  on the password and salt. Only for side-channel-free
@@ -376,7 +424,7 @@ extern "C" {
     /// @param  context  Pointer to current Argon2 context
     ///
     /// @return  Zero if successful, a non zero error code otherwise
-    pub fn argon2d_ctx(context: *mut argon2_context) -> libc::c_int;
+    pub fn argon2d_ctx(context: *mut argon2_context) -> core::ffi::c_int;
 
     /// Argon2i: Version of Argon2 that picks memory blocks
     /// independent on the password and salt. Good for side-channels,
@@ -385,7 +433,7 @@ extern "C" {
     /// @param  context  Pointer to current Argon2 context
     ///
     /// @return  Zero if successful, a non zero error code otherwise
-    pub fn argon2i_ctx(context: *mut argon2_context) -> libc::c_int;
+    pub fn argon2i_ctx(context: *mut argon2_context) -> core::ffi::c_int;
 
     /// Argon2id: Version of Argon2 where the first half-pass over memory is
     /// password-independent, the rest are password-dependent (on the password and
@@ -395,7 +443,7 @@ extern "C" {
     /// @param  context  Pointer to current Argon2 context
     ///
     /// @return  Zero if successful, a non zero error code otherwise
-    pub fn argon2id_ctx(context: *mut argon2_context) -> libc::c_int;
+    pub fn argon2id_ctx(context: *mut argon2_context) -> core::ffi::c_int;
 
     /// Verify if a given password is correct for Argon2d hashing
     ///
@@ -407,8 +455,8 @@ extern "C" {
     /// @return  Zero if successful, a non zero error code otherwise
     pub fn argon2d_verify_ctx(
         context: *mut argon2_context,
-        hash: *const libc::c_char,
-    ) -> libc::c_int;
+        hash: *const core::ffi::c_char,
+    ) -> core::ffi::c_int;
 
     /// Verify if a given password is correct for Argon2i hashing
     /// @param  context  Pointer to current Argon2 context
@@ -419,8 +467,8 @@ extern "C" {
     /// @return  Zero if successful, a non zero error code otherwise
     pub fn argon2i_verify_ctx(
         context: *mut argon2_context,
-        hash: *const libc::c_char,
-    ) -> libc::c_int;
+        hash: *const core::ffi::c_char,
+    ) -> core::ffi::c_int;
 
     /// Verify if a given password is correct for Argon2id hashing
     ///
@@ -432,19 +480,19 @@ extern "C" {
     /// @return  Zero if successful, a non zero error code otherwise
     pub fn argon2id_verify_ctx(
         context: *mut argon2_context,
-        hash: *const libc::c_char,
-    ) -> libc::c_int;
+        hash: *const core::ffi::c_char,
+    ) -> core::ffi::c_int;
 
     pub fn argon2_verify_ctx(
         context: *mut argon2_context,
-        hash: *const libc::c_char,
+        hash: *const core::ffi::c_char,
         ty: argon2_type,
-    ) -> libc::c_int;
+    ) -> core::ffi::c_int;
 
     /// Get the associated error message for given error code
     ///
     /// @return  The error message associated with the given error code
-    pub fn argon2_error_message(error_code: libc::c_int) -> *const libc::c_char;
+    pub fn argon2_error_message(error_code: core::ffi::c_int) -> *const core::ffi::c_char;
 
     /// Returns the encoded hash length for the given input parameters
     ///
@@ -468,5 +516,110 @@ extern "C" {
         saltlen: u32,
         hashlen: u32,
         ty: argon2_type,
-    ) -> libc::size_t;
+    ) -> usize;
+}
+
+pub const BLAKE2B_BLOCKBYTES: usize = 128;
+pub const BLAKE2B_OUTBYTES: usize = 64;
+pub const BLAKE2B_KEYBYTES: usize = 64;
+pub const BLAKE2B_SALTBYTES: usize = 16;
+pub const BLAKE2B_PERSONALBYTES: usize = 16;
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct blake2b_param {
+    pub digest_length: u8,
+    pub key_length: u8,
+    pub fanout: u8,
+    pub depth: u8,
+    pub leaf_length: u32,
+    pub node_offset: u64,
+    pub node_depth: u8,
+    pub inner_length: u8,
+    pub reserved: [u8; 14],
+    pub salt: [u8; BLAKE2B_SALTBYTES],
+    pub personal: [u8; BLAKE2B_PERSONALBYTES],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct blake2b_state {
+    pub h: [u64; 8],
+    pub t: [u64; 2],
+    pub f: [u64; 2],
+    pub buf: [u8; BLAKE2B_BLOCKBYTES],
+    pub buflen: core::ffi::c_uint,
+    pub outlen: core::ffi::c_uint,
+    pub last_node: u8,
+}
+
+// The reference build marks these hidden, so they are only available when
+// linking the vendored sources or a static library.
+#[cfg(argon2_sys_blake2b)]
+extern "C" {
+    /// Initializes an unkeyed state producing `outlen` bytes (1 to 64).
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b_init(state: *mut blake2b_state, outlen: usize) -> core::ffi::c_int;
+
+    /// Initializes a keyed state producing `outlen` bytes (1 to 64), with a
+    /// key of 1 to 64 bytes.
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b_init_key(
+        state: *mut blake2b_state,
+        outlen: usize,
+        key: *const core::ffi::c_void,
+        keylen: usize,
+    ) -> core::ffi::c_int;
+
+    /// Initializes a state from a full parameter block.
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b_init_param(
+        state: *mut blake2b_state,
+        param: *const blake2b_param,
+    ) -> core::ffi::c_int;
+
+    /// Absorbs `inlen` bytes of input.
+    ///
+    /// @return 0 if successful, -1 if the state has already been finalized
+    pub fn blake2b_update(
+        state: *mut blake2b_state,
+        input: *const core::ffi::c_void,
+        inlen: usize,
+    ) -> core::ffi::c_int;
+
+    /// Writes the digest to `out`, which must hold at least the `outlen` the
+    /// state was initialized with, and wipes the state.
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b_final(
+        state: *mut blake2b_state,
+        out: *mut core::ffi::c_void,
+        outlen: usize,
+    ) -> core::ffi::c_int;
+
+    /// Computes a digest in one call. `key` may be NULL if `keylen` is 0.
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b(
+        out: *mut core::ffi::c_void,
+        outlen: usize,
+        input: *const core::ffi::c_void,
+        inlen: usize,
+        key: *const core::ffi::c_void,
+        keylen: usize,
+    ) -> core::ffi::c_int;
+
+    /// Variable-length hash function H' from the Argon2 specification, which
+    /// can produce up to 2^32 - 1 bytes of output.
+    ///
+    /// @return 0 if successful, -1 otherwise
+    pub fn blake2b_long(
+        out: *mut core::ffi::c_void,
+        outlen: usize,
+        input: *const core::ffi::c_void,
+        inlen: usize,
+    ) -> core::ffi::c_int;
 }
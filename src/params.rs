@@ -0,0 +1,31 @@
+use crate::{Argon2_id, Argon2_type, Argon2_version, ARGON2_VERSION_13};
+
+/// The algorithm and cost parameters used to produce a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub ty: Argon2_type,
+    pub version: Argon2_version,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Memory usage in kibibytes
+    pub m_cost: u32,
+    /// Number of lanes
+    pub lanes: u32,
+    /// Length of the hash in bytes
+    pub hash_len: u32,
+}
+
+impl Default for Params {
+    /// Argon2id, version 0x13, with the cost defaults of the reference
+    /// `argon2` command-line tool (3 iterations, 4 MiB, 1 lane, 32 bytes).
+    fn default() -> Params {
+        Params {
+            ty: Argon2_id,
+            version: ARGON2_VERSION_13,
+            t_cost: 3,
+            m_cost: 1 << 12,
+            lanes: 1,
+            hash_len: 32,
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Parsing and formatting of Argon2 hashes in the PHC string format, e.g.
+//! `$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc`.
+//!
+//! The accepted syntax is the one produced and consumed by `encoding.c`:
+//! decimals without leading zeros, and unpadded standard base64 without
+//! stray trailing bits. Like `encoding.c`, the `v=` field may be omitted,
+//! as it is in hashes written before version 0x13 existed; such hashes are
+//! version 0x10.
+
+use crate::error::{Error, Result};
+use crate::params::Params;
+use crate::{
+    Argon2_d, Argon2_i, Argon2_id, Argon2_type, Argon2_version, ARGON2_DECODING_FAIL,
+    ARGON2_INCORRECT_TYPE, ARGON2_VERSION_10,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// The parameters, salt and hash of an encoded Argon2 hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhcHash {
+    pub ty: Argon2_type,
+    pub version: Argon2_version,
+    /// Whether the string has a `v=` field. Hashes written before version
+    /// 0x13 existed leave it out, and are formatted back without it.
+    pub explicit_version: bool,
+    /// Memory usage in kibibytes
+    pub m_cost: u32,
+    /// Number of iterations
+    pub t_cost: u32,
+    /// Number of lanes
+    pub lanes: u32,
+    pub salt: Vec<u8>,
+    pub hash: Vec<u8>,
+}
+
+impl PhcHash {
+    /// Parses an encoded Argon2 hash.
+    ///
+    /// Fails with `ARGON2_INCORRECT_TYPE` if the identifier is not one of
+    /// `argon2d`, `argon2i` or `argon2id`, and with `ARGON2_DECODING_FAIL`
+    /// if the string is otherwise malformed.
+    pub fn parse(encoded: &str) -> Result<PhcHash> {
+        let ty = identifier(encoded)
+            .and_then(type_from_identifier)
+            .ok_or(Error::Argon2(ARGON2_INCORRECT_TYPE))?;
+        parse_fields(encoded, ty).ok_or(Error::Argon2(ARGON2_DECODING_FAIL))
+    }
+
+    /// Returns the parameters this hash was produced with.
+    pub fn params(&self) -> Params {
+        Params {
+            ty: self.ty,
+            version: self.version,
+            t_cost: self.t_cost,
+            m_cost: self.m_cost,
+            lanes: self.lanes,
+            hash_len: self.hash.len() as u32,
+        }
+    }
+
+    /// Returns whether this hash was produced with parameters other than
+    /// `policy`, and should be replaced by a new hash on the next successful
+    /// login.
+    pub fn needs_rehash(&self, policy: &Params) -> bool {
+        self.params() != *policy
+    }
+}
+
+impl FromStr for PhcHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<PhcHash> {
+        PhcHash::parse(s)
+    }
+}
+
+/// Formats the hash the way `encoding.c` does. Fails if `ty` is not one of
+/// `Argon2_d`, `Argon2_i` or `Argon2_id`.
+impl fmt::Display for PhcHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${}", identifier_of(self.ty).ok_or(fmt::Error)?)?;
+        if self.explicit_version {
+            write!(f, "$v={}", self.version)?;
+        }
+        write!(
+            f,
+            "$m={},t={},p={}${}${}",
+            self.m_cost,
+            self.t_cost,
+            self.lanes,
+            base64_encode(&self.salt),
+            base64_encode(&self.hash),
+        )
+    }
+}
+
+/// Returns the algorithm identifier of a PHC string, i.e. `argon2id` in
+/// `$argon2id$v=19$...`.
+pub fn identifier(encoded: &str) -> Option<&str> {
+    let rest = encoded.strip_prefix('$')?;
+    let end = rest.find('$').unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// Returns the `Argon2_type` for an identifier such as `argon2id`.
+pub fn type_from_identifier(identifier: &str) -> Option<Argon2_type> {
+    match identifier {
+        "argon2d" => Some(Argon2_d),
+        "argon2i" => Some(Argon2_i),
+        "argon2id" => Some(Argon2_id),
+        _ => None,
+    }
+}
+
+fn identifier_of(ty: Argon2_type) -> Option<&'static str> {
+    match ty {
+        Argon2_d => Some("argon2d"),
+        Argon2_i => Some("argon2i"),
+        Argon2_id => Some("argon2id"),
+        _ => None,
+    }
+}
+
+fn parse_fields(encoded: &str, ty: Argon2_type) -> Option<PhcHash> {
+    let mut fields = encoded.split('$').skip(2).peekable();
+    let (version, explicit_version) = match fields.peek()?.strip_prefix("v=") {
+        Some(version) => {
+            let version = decimal(version)?;
+            fields.next();
+            (version, true)
+        }
+        None => (ARGON2_VERSION_10, false),
+    };
+
+    let mut params = fields.next()?.split(',');
+    let m_cost = decimal(params.next()?.strip_prefix("m=")?)?;
+    let t_cost = decimal(params.next()?.strip_prefix("t=")?)?;
+    let lanes = decimal(params.next()?.strip_prefix("p=")?)?;
+    if params.next().is_some() {
+        return None;
+    }
+
+    let salt = base64_decode(fields.next()?)?;
+    let hash = base64_decode(fields.next()?)?;
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some(PhcHash {
+        ty,
+        version,
+        explicit_version,
+        m_cost,
+        t_cost,
+        lanes,
+        salt,
+        hash,
+    })
+}
+
+/// Parses a `u32` in the canonical form written by `encoding.c`.
+fn decimal(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if s.len() > 1 && s.starts_with('0') {
+        return None;
+    }
+    s.parse().ok()
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let n = (u32::from(buf[0]) << 16) | (u32::from(buf[1]) << 8) | u32::from(buf[2]);
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut acc_len = 0;
+    for c in s.bytes() {
+        let value = ALPHABET.iter().position(|&a| a == c)? as u32;
+        acc = (acc << 6) | value;
+        acc_len += 6;
+        if acc_len >= 8 {
+            acc_len -= 8;
+            out.push((acc >> acc_len) as u8);
+            acc &= (1 << acc_len) - 1;
+        }
+    }
+    // A single leftover character, or non-zero padding bits, cannot have
+    // been produced by the encoder.
+    if acc_len > 4 || acc != 0 {
+        return None;
+    }
+    Some(out)
+}
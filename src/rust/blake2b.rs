@@ -0,0 +1,200 @@
+//! BLAKE2b as specified in RFC 7693, and the variable-length hash function
+//! H' from the Argon2 specification.
+
+const BLOCKBYTES: usize = 128;
+const OUTBYTES: usize = 64;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+/// A streaming BLAKE2b state with an output length of 1 to 64 bytes and an
+/// optional key of up to 64 bytes.
+#[derive(Clone)]
+pub struct State {
+    h: [u64; 8],
+    t: u128,
+    buf: [u8; BLOCKBYTES],
+    buflen: usize,
+    outlen: usize,
+}
+
+impl State {
+    /// # Panics
+    ///
+    /// Panics if `outlen` is not between 1 and 64, or `key` is longer than
+    /// 64 bytes.
+    pub fn new(outlen: usize, key: &[u8]) -> State {
+        assert!((1..=OUTBYTES).contains(&outlen));
+        assert!(key.len() <= OUTBYTES);
+
+        let mut h = IV;
+        h[0] ^= 0x01010000 ^ ((key.len() as u64) << 8) ^ outlen as u64;
+        let mut state = State {
+            h,
+            t: 0,
+            buf: [0; BLOCKBYTES],
+            buflen: 0,
+            outlen,
+        };
+        if !key.is_empty() {
+            let mut block = [0u8; BLOCKBYTES];
+            block[..key.len()].copy_from_slice(key);
+            state.update(&block);
+            super::wipe(&mut block);
+        }
+        state
+    }
+
+    #[cfg(argon2_sys_rust_backend)]
+    pub fn outlen(&self) -> usize {
+        self.outlen
+    }
+
+    pub fn update(&mut self, mut input: &[u8]) -> &mut State {
+        while !input.is_empty() {
+            // The last block is only compressed in `finalize`, with the
+            // final block flag set, so a full buffer is kept until more
+            // input arrives.
+            if self.buflen == BLOCKBYTES {
+                self.t += BLOCKBYTES as u128;
+                let block = self.buf;
+                self.compress(&block, false);
+                self.buflen = 0;
+            }
+            let take = (BLOCKBYTES - self.buflen).min(input.len());
+            self.buf[self.buflen..self.buflen + take].copy_from_slice(&input[..take]);
+            self.buflen += take;
+            input = &input[take..];
+        }
+        self
+    }
+
+    /// Writes the digest to the first `outlen` bytes of `out`.
+    pub fn finalize(&mut self, out: &mut [u8]) {
+        self.t += self.buflen as u128;
+        for byte in &mut self.buf[self.buflen..] {
+            *byte = 0;
+        }
+        let block = self.buf;
+        self.compress(&block, true);
+
+        let mut bytes = [0u8; OUTBYTES];
+        for (chunk, word) in bytes.chunks_exact_mut(8).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out[..self.outlen].copy_from_slice(&bytes[..self.outlen]);
+        super::wipe(&mut bytes);
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCKBYTES], last: bool) {
+        let mut m = [0u64; 16];
+        for (word, chunk) in m.iter_mut().zip(block.chunks_exact(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(bytes);
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.t as u64;
+        v[13] ^= (self.t >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for s in &SIGMA {
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        super::wipe(&mut self.buf);
+    }
+}
+
+fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Fills `out` with H'(`input`), where `input` is the concatenation of the
+/// given slices. `out` must not be longer than `u32::MAX` bytes.
+pub fn long(out: &mut [u8], input: &[&[u8]]) {
+    let outlen = (out.len() as u32).to_le_bytes();
+
+    if out.len() <= OUTBYTES {
+        let mut state = State::new(out.len(), &[]);
+        state.update(&outlen);
+        for part in input {
+            state.update(part);
+        }
+        state.finalize(out);
+        return;
+    }
+
+    let mut v = [0u8; OUTBYTES];
+    let mut state = State::new(OUTBYTES, &[]);
+    state.update(&outlen);
+    for part in input {
+        state.update(part);
+    }
+    state.finalize(&mut v);
+
+    let mut pos = 0;
+    loop {
+        out[pos..pos + OUTBYTES / 2].copy_from_slice(&v[..OUTBYTES / 2]);
+        pos += OUTBYTES / 2;
+        let rest = out.len() - pos;
+        let mut state = State::new(rest.min(OUTBYTES), &[]);
+        state.update(&v);
+        if rest <= OUTBYTES {
+            state.finalize(&mut out[pos..]);
+            break;
+        }
+        state.finalize(&mut v);
+    }
+    super::wipe(&mut v);
+}
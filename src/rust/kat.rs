@@ -0,0 +1,112 @@
+//! Known-answer test output in the format of the reference `genkat` tool,
+//! which prints the pre-hashing digest, the memory after every pass and
+//! the tag. Its output for each variant and version is checked in as
+//! `argon2/kats/argon2{d,i,id}{,_v16}`.
+
+use super::{hash_traced, Block, Trace, PREHASH_DIGEST_LENGTH, QWORDS_IN_BLOCK};
+use crate::error::Result;
+use crate::params::Params;
+use crate::{Argon2_d, Argon2_i, Argon2_type, Argon2_version};
+use alloc::string::String;
+use alloc::vec;
+use core::fmt::Write;
+
+/// Returns what `genkat <type> <version>` prints: a trace of hashing 32
+/// bytes of 0x01 with 16 bytes of 0x02 as salt, 8 bytes of 0x03 as secret
+/// and 12 bytes of 0x04 as associated data, using 32 KiB, 3 passes and 4
+/// lanes, into a 32-byte tag.
+pub fn genkat(ty: Argon2_type, version: Argon2_version) -> Result<String> {
+    let params = Params {
+        ty,
+        version,
+        t_cost: 3,
+        m_cost: 32,
+        lanes: 4,
+        hash_len: 32,
+    };
+    trace(&params, &[1; 32], &[2; 16], &[3; 8], &[4; 12])
+}
+
+/// Returns a trace of hashing the given inputs, in the format of `genkat`.
+///
+/// Every word of every block is printed if there are at most 128 blocks,
+/// and only the first word of each block otherwise.
+pub fn trace(params: &Params, pwd: &[u8], salt: &[u8], secret: &[u8], ad: &[u8]) -> Result<String> {
+    let mut kat = Kat {
+        params,
+        pwd,
+        salt,
+        secret,
+        ad,
+        output: String::new(),
+    };
+    let mut out = vec![0u8; params.hash_len as usize];
+    hash_traced(params, pwd, salt, secret, ad, &mut out, &mut kat)?;
+    Ok(kat.output)
+}
+
+struct Kat<'a> {
+    params: &'a Params,
+    pwd: &'a [u8],
+    salt: &'a [u8],
+    secret: &'a [u8],
+    ad: &'a [u8],
+    output: String,
+}
+
+impl Kat<'_> {
+    fn bytes(&mut self, label: &str, bytes: &[u8]) {
+        self.output.push_str(label);
+        for byte in bytes {
+            let _ = write!(self.output, "{:02x} ", byte);
+        }
+        self.output.push('\n');
+    }
+}
+
+impl Trace for Kat<'_> {
+    fn initial_hash(&mut self, h0: &[u8]) {
+        let name = match self.params.ty {
+            Argon2_d => "Argon2d",
+            Argon2_i => "Argon2i",
+            _ => "Argon2id",
+        };
+        let params = self.params;
+        let _ = write!(
+            self.output,
+            "=======================================\n\
+             {} version number {}\n\
+             =======================================\n\
+             Memory: {} KiB, Iterations: {}, Parallelism: {} lanes, Tag length: {} bytes\n",
+            name, params.version, params.m_cost, params.t_cost, params.lanes, params.hash_len,
+        );
+        let inputs = [
+            ("Password", self.pwd),
+            ("Salt", self.salt),
+            ("Secret", self.secret),
+            ("Associated data", self.ad),
+        ];
+        for (label, bytes) in &inputs {
+            self.bytes(&alloc::format!("{}[{}]: ", label, bytes.len()), bytes);
+        }
+        self.bytes("Pre-hashing digest: ", &h0[..PREHASH_DIGEST_LENGTH]);
+    }
+
+    fn pass(&mut self, pass: u32, blocks: &[Block]) {
+        let _ = writeln!(self.output, "\n After pass {}:", pass);
+        let words = if blocks.len() > QWORDS_IN_BLOCK {
+            1
+        } else {
+            QWORDS_IN_BLOCK
+        };
+        for (i, block) in blocks.iter().enumerate() {
+            for (j, word) in block.0[..words].iter().enumerate() {
+                let _ = writeln!(self.output, "Block {:04} [{:3}]: {:016x}", i, j, word);
+            }
+        }
+    }
+
+    fn tag(&mut self, tag: &[u8]) {
+        self.bytes("Tag: ", tag);
+    }
+}
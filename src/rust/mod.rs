@@ -0,0 +1,535 @@
+//! A pure-Rust implementation of Argon2, for targets where the C sources
+//! cannot be compiled.
+//!
+//! It follows `ref.c` and `core.c` closely, including the order in which
+//! inputs are validated, so it returns the same outputs and error codes as
+//! the C library. Lanes are filled one after another rather than in
+//! parallel, which gives the same result. With the `rust-backend` feature
+//! it backs the safe API in place of the C library.
+
+pub(crate) mod blake2b;
+pub mod kat;
+
+use crate::error::{check, Result};
+use crate::kdf::wipe;
+use crate::params::Params;
+use crate::*;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+const BLOCK_SIZE: usize = 1024;
+const QWORDS_IN_BLOCK: usize = BLOCK_SIZE / 8;
+const PREHASH_DIGEST_LENGTH: usize = 64;
+const PREHASH_SEED_LENGTH: usize = PREHASH_DIGEST_LENGTH + 8;
+const SYNC_POINTS: u32 = ARGON2_SYNC_POINTS as u32;
+
+/// Computes the raw Argon2 hash of the given inputs into `out`.
+///
+/// This is the equivalent of `argon2_ctx` with `threads` equal to `lanes`.
+pub fn hash(
+    params: &Params,
+    pwd: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out: &mut [u8],
+) -> Result<()> {
+    hash_traced(params, pwd, salt, secret, ad, out, &mut ())
+}
+
+/// Receives the intermediate values of a computation, for `kat`.
+trait Trace {
+    fn initial_hash(&mut self, _h0: &[u8]) {}
+    fn pass(&mut self, _pass: u32, _blocks: &[Block]) {}
+    fn tag(&mut self, _tag: &[u8]) {}
+}
+
+impl Trace for () {}
+
+fn hash_traced(
+    params: &Params,
+    pwd: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out: &mut [u8],
+    trace: &mut dyn Trace,
+) -> Result<()> {
+    validate(params, pwd, salt, secret, ad, out)?;
+    if params.ty != Argon2_d && params.ty != Argon2_i && params.ty != Argon2_id {
+        return Err(Error::Argon2(ARGON2_INCORRECT_TYPE));
+    }
+
+    let instance = Instance::new(params);
+    let mut blocks = instance.allocate()?;
+    let mut h0 = initial_hash(params, pwd, salt, secret, ad, out.len());
+    trace.initial_hash(&h0[..PREHASH_DIGEST_LENGTH]);
+    instance.fill_first_blocks(&mut blocks, &h0);
+    wipe(&mut h0);
+    instance.fill_memory_blocks(&mut blocks, trace);
+    instance.finalize(&blocks, out);
+    trace.tag(out);
+    for block in &mut blocks {
+        block.wipe();
+    }
+    Ok(())
+}
+
+/// Returns the message `argon2_error_message` returns for `code`.
+pub fn error_message(code: Argon2_ErrorCodes) -> &'static str {
+    match code {
+        ARGON2_OK => "OK",
+        ARGON2_OUTPUT_PTR_NULL => "Output pointer is NULL",
+        ARGON2_OUTPUT_TOO_SHORT => "Output is too short",
+        ARGON2_OUTPUT_TOO_LONG => "Output is too long",
+        ARGON2_PWD_TOO_SHORT => "Password is too short",
+        ARGON2_PWD_TOO_LONG => "Password is too long",
+        ARGON2_SALT_TOO_SHORT => "Salt is too short",
+        ARGON2_SALT_TOO_LONG => "Salt is too long",
+        ARGON2_AD_TOO_SHORT => "Associated data is too short",
+        ARGON2_AD_TOO_LONG => "Associated data is too long",
+        ARGON2_SECRET_TOO_SHORT => "Secret is too short",
+        ARGON2_SECRET_TOO_LONG => "Secret is too long",
+        ARGON2_TIME_TOO_SMALL => "Time cost is too small",
+        ARGON2_TIME_TOO_LARGE => "Time cost is too large",
+        ARGON2_MEMORY_TOO_LITTLE => "Memory cost is too small",
+        ARGON2_MEMORY_TOO_MUCH => "Memory cost is too large",
+        ARGON2_LANES_TOO_FEW => "Too few lanes",
+        ARGON2_LANES_TOO_MANY => "Too many lanes",
+        ARGON2_PWD_PTR_MISMATCH => "Password pointer is NULL, but password length is not 0",
+        ARGON2_SALT_PTR_MISMATCH => "Salt pointer is NULL, but salt length is not 0",
+        ARGON2_SECRET_PTR_MISMATCH => "Secret pointer is NULL, but secret length is not 0",
+        ARGON2_AD_PTR_MISMATCH => "Associated data pointer is NULL, but ad length is not 0",
+        ARGON2_MEMORY_ALLOCATION_ERROR => "Memory allocation error",
+        ARGON2_FREE_MEMORY_CBK_NULL => "The free memory callback is NULL",
+        ARGON2_ALLOCATE_MEMORY_CBK_NULL => "The allocate memory callback is NULL",
+        ARGON2_INCORRECT_PARAMETER => "Argon2_Context context is NULL",
+        ARGON2_INCORRECT_TYPE => "There is no such version of Argon2",
+        ARGON2_OUT_PTR_MISMATCH => "Output pointer mismatch",
+        ARGON2_THREADS_TOO_FEW => "Not enough threads",
+        ARGON2_THREADS_TOO_MANY => "Too many threads",
+        ARGON2_MISSING_ARGS => "Missing arguments",
+        ARGON2_ENCODING_FAIL => "Encoding failed",
+        ARGON2_DECODING_FAIL => "Decoding failed",
+        ARGON2_THREAD_FAIL => "Threading failure",
+        ARGON2_DECODING_LENGTH_FAIL => "Some of encoded parameters are too long or too short",
+        ARGON2_VERIFY_MISMATCH => "The password does not match the supplied hash",
+        _ => "Unknown error code",
+    }
+}
+
+/// Mirrors `validate_inputs` in `core.c`, returning the same error for the
+/// first invalid input. Slices cannot be null, so the pointer checks are
+/// left out.
+fn validate(
+    params: &Params,
+    pwd: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out: &[u8],
+) -> Result<()> {
+    fn check_len(
+        bytes: &[u8],
+        min: u64,
+        max: u64,
+        short: Argon2_ErrorCodes,
+        long: Argon2_ErrorCodes,
+    ) -> Result<()> {
+        let len = bytes.len() as u64;
+        if len < min {
+            return check(short);
+        }
+        if len > max {
+            return check(long);
+        }
+        Ok(())
+    }
+
+    let out_len = u64::try_from(out.len()).unwrap_or(u64::MAX);
+    if out_len < ARGON2_MIN_OUTLEN {
+        return check(ARGON2_OUTPUT_TOO_SHORT);
+    }
+    if out_len > ARGON2_MAX_OUTLEN {
+        return check(ARGON2_OUTPUT_TOO_LONG);
+    }
+    check_len(
+        pwd,
+        ARGON2_MIN_PWD_LENGTH,
+        ARGON2_MAX_PWD_LENGTH,
+        ARGON2_PWD_TOO_SHORT,
+        ARGON2_PWD_TOO_LONG,
+    )?;
+    check_len(
+        salt,
+        ARGON2_MIN_SALT_LENGTH,
+        ARGON2_MAX_SALT_LENGTH,
+        ARGON2_SALT_TOO_SHORT,
+        ARGON2_SALT_TOO_LONG,
+    )?;
+    check_len(
+        secret,
+        ARGON2_MIN_SECRET,
+        ARGON2_MAX_SECRET,
+        ARGON2_SECRET_TOO_SHORT,
+        ARGON2_SECRET_TOO_LONG,
+    )?;
+    check_len(
+        ad,
+        ARGON2_MIN_AD_LENGTH,
+        ARGON2_MAX_AD_LENGTH,
+        ARGON2_AD_TOO_SHORT,
+        ARGON2_AD_TOO_LONG,
+    )?;
+
+    let m_cost = u64::from(params.m_cost);
+    let lanes = u64::from(params.lanes);
+    if m_cost < ARGON2_MIN_MEMORY {
+        return check(ARGON2_MEMORY_TOO_LITTLE);
+    }
+    if m_cost > ARGON2_MAX_MEMORY {
+        return check(ARGON2_MEMORY_TOO_MUCH);
+    }
+    if m_cost < 8 * lanes {
+        return check(ARGON2_MEMORY_TOO_LITTLE);
+    }
+
+    let t_cost = u64::from(params.t_cost);
+    if t_cost < ARGON2_MIN_TIME {
+        return check(ARGON2_TIME_TOO_SMALL);
+    }
+    if t_cost > ARGON2_MAX_TIME {
+        return check(ARGON2_TIME_TOO_LARGE);
+    }
+
+    if lanes < ARGON2_MIN_LANES {
+        return check(ARGON2_LANES_TOO_FEW);
+    }
+    if lanes > ARGON2_MAX_LANES {
+        return check(ARGON2_LANES_TOO_MANY);
+    }
+    // The thread count equals the lane count, so the thread checks of
+    // `validate_inputs` cannot fail here.
+    Ok(())
+}
+
+/// H0, the BLAKE2b hash of the parameters and inputs.
+fn initial_hash(
+    params: &Params,
+    pwd: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out_len: usize,
+) -> [u8; PREHASH_SEED_LENGTH] {
+    let mut state = blake2b::State::new(PREHASH_DIGEST_LENGTH, &[]);
+    for value in &[
+        params.lanes,
+        out_len as u32,
+        params.m_cost,
+        params.t_cost,
+        params.version,
+        params.ty,
+    ] {
+        state.update(&value.to_le_bytes());
+    }
+    for input in &[pwd, salt, secret, ad] {
+        state.update(&(input.len() as u32).to_le_bytes());
+        state.update(input);
+    }
+
+    let mut seed = [0u8; PREHASH_SEED_LENGTH];
+    state.finalize(&mut seed);
+    seed
+}
+
+#[derive(Clone, Copy)]
+struct Block([u64; QWORDS_IN_BLOCK]);
+
+impl Block {
+    const ZERO: Block = Block([0; QWORDS_IN_BLOCK]);
+
+    fn from_bytes(bytes: &[u8]) -> Block {
+        let mut block = Block::ZERO;
+        for (word, chunk) in block.0.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut le = [0u8; 8];
+            le.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(le);
+        }
+        block
+    }
+
+    fn to_bytes(self) -> [u8; BLOCK_SIZE] {
+        let mut bytes = [0u8; BLOCK_SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn xor(&mut self, other: &Block) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a ^= b;
+        }
+    }
+
+    fn wipe(&mut self) {
+        for word in self.0.iter_mut() {
+            unsafe { core::ptr::write_volatile(word, 0) };
+        }
+    }
+}
+
+/// The derived dimensions of the memory, as in `argon2_instance_t`.
+struct Instance {
+    version: Argon2_version,
+    ty: Argon2_type,
+    passes: u32,
+    lanes: u32,
+    memory_blocks: u32,
+    segment_length: u32,
+    lane_length: u32,
+}
+
+/// A segment being filled, as in `argon2_position_t`.
+#[derive(Clone, Copy)]
+struct Position {
+    pass: u32,
+    lane: u32,
+    slice: u32,
+    index: u32,
+}
+
+impl Instance {
+    fn new(params: &Params) -> Instance {
+        let lanes = params.lanes;
+        // At least two blocks per segment, rounded down to a multiple of
+        // the segment count.
+        let memory_blocks = params.m_cost.max(2 * SYNC_POINTS * lanes);
+        let segment_length = memory_blocks / (lanes * SYNC_POINTS);
+        Instance {
+            version: params.version,
+            ty: params.ty,
+            passes: params.t_cost,
+            lanes,
+            memory_blocks: segment_length * lanes * SYNC_POINTS,
+            segment_length,
+            lane_length: segment_length * SYNC_POINTS,
+        }
+    }
+
+    fn allocate(&self) -> Result<Vec<Block>> {
+        let count = self.memory_blocks as usize;
+        let mut blocks = Vec::new();
+        blocks
+            .try_reserve_exact(count)
+            .map_err(|_| Error::Argon2(ARGON2_MEMORY_ALLOCATION_ERROR))?;
+        blocks.resize(count, Block::ZERO);
+        Ok(blocks)
+    }
+
+    fn fill_first_blocks(&self, blocks: &mut [Block], h0: &[u8; PREHASH_SEED_LENGTH]) {
+        let mut seed = *h0;
+        let mut bytes = [0u8; BLOCK_SIZE];
+        for lane in 0..self.lanes {
+            seed[PREHASH_DIGEST_LENGTH + 4..].copy_from_slice(&lane.to_le_bytes());
+            for index in 0..2u32 {
+                seed[PREHASH_DIGEST_LENGTH..PREHASH_DIGEST_LENGTH + 4]
+                    .copy_from_slice(&index.to_le_bytes());
+                blake2b::long(&mut bytes, &[&seed]);
+                blocks[(lane * self.lane_length + index) as usize] = Block::from_bytes(&bytes);
+            }
+        }
+        wipe(&mut seed);
+        wipe(&mut bytes);
+    }
+
+    fn fill_memory_blocks(&self, blocks: &mut [Block], trace: &mut dyn Trace) {
+        for pass in 0..self.passes {
+            for slice in 0..SYNC_POINTS {
+                for lane in 0..self.lanes {
+                    let position = Position {
+                        pass,
+                        lane,
+                        slice,
+                        index: 0,
+                    };
+                    self.fill_segment(blocks, position);
+                }
+            }
+            trace.pass(pass, blocks);
+        }
+    }
+
+    fn fill_segment(&self, blocks: &mut [Block], mut position: Position) {
+        let data_independent = self.ty == Argon2_i
+            || (self.ty == Argon2_id && position.pass == 0 && position.slice < SYNC_POINTS / 2);
+
+        let mut address = Block::ZERO;
+        let mut input = Block::ZERO;
+        if data_independent {
+            input.0[0] = u64::from(position.pass);
+            input.0[1] = u64::from(position.lane);
+            input.0[2] = u64::from(position.slice);
+            input.0[3] = u64::from(self.memory_blocks);
+            input.0[4] = u64::from(self.passes);
+            input.0[5] = u64::from(self.ty);
+        }
+
+        let mut starting_index = 0;
+        if position.pass == 0 && position.slice == 0 {
+            // The first two blocks of each lane are already filled.
+            starting_index = 2;
+            if data_independent {
+                next_addresses(&mut address, &mut input);
+            }
+        }
+
+        let segment_start = position.lane * self.lane_length + position.slice * self.segment_length;
+        for index in starting_index..self.segment_length {
+            let curr_offset = segment_start + index;
+            let prev_offset = if curr_offset % self.lane_length == 0 {
+                // The first block of a lane follows the last one.
+                curr_offset + self.lane_length - 1
+            } else {
+                curr_offset - 1
+            };
+
+            let pseudo_rand = if data_independent {
+                if index as usize % QWORDS_IN_BLOCK == 0 {
+                    next_addresses(&mut address, &mut input);
+                }
+                address.0[index as usize % QWORDS_IN_BLOCK]
+            } else {
+                blocks[prev_offset as usize].0[0]
+            };
+
+            let ref_lane = if position.pass == 0 && position.slice == 0 {
+                // Nothing has been written to the other lanes yet.
+                position.lane
+            } else {
+                ((pseudo_rand >> 32) % u64::from(self.lanes)) as u32
+            };
+            position.index = index;
+            let ref_index =
+                self.index_alpha(position, pseudo_rand as u32, ref_lane == position.lane);
+
+            let prev = blocks[prev_offset as usize];
+            let reference = blocks[(self.lane_length * ref_lane + ref_index) as usize];
+            let with_xor = self.version != ARGON2_VERSION_10 && position.pass != 0;
+            fill_block(
+                &prev,
+                &reference,
+                &mut blocks[curr_offset as usize],
+                with_xor,
+            );
+        }
+    }
+
+    /// Maps a pseudo-random value to the index of the reference block
+    /// within its lane.
+    fn index_alpha(&self, position: Position, pseudo_rand: u32, same_lane: bool) -> u32 {
+        let segment_length = u64::from(self.segment_length);
+        let lane_length = u64::from(self.lane_length);
+        let index = u64::from(position.index);
+        let reference_area_size = if position.pass == 0 {
+            if position.slice == 0 {
+                index - 1
+            } else if same_lane {
+                u64::from(position.slice) * segment_length + index - 1
+            } else {
+                u64::from(position.slice) * segment_length - u64::from(index == 0)
+            }
+        } else if same_lane {
+            lane_length - segment_length + index - 1
+        } else {
+            lane_length - segment_length - u64::from(index == 0)
+        };
+
+        let mut relative_position = u64::from(pseudo_rand);
+        relative_position = (relative_position * relative_position) >> 32;
+        relative_position =
+            reference_area_size - 1 - ((reference_area_size * relative_position) >> 32);
+
+        let start_position = if position.pass != 0 && position.slice != SYNC_POINTS - 1 {
+            u64::from(position.slice + 1) * segment_length
+        } else {
+            0
+        };
+
+        ((start_position + relative_position) % lane_length) as u32
+    }
+
+    fn finalize(&self, blocks: &[Block], out: &mut [u8]) {
+        let last = self.lane_length as usize - 1;
+        let mut block = blocks[last];
+        for lane in 1..self.lanes as usize {
+            block.xor(&blocks[lane * self.lane_length as usize + last]);
+        }
+        let mut bytes = block.to_bytes();
+        blake2b::long(out, &[&bytes]);
+        block.wipe();
+        wipe(&mut bytes);
+    }
+}
+
+/// Generates the next 128 pseudo-random values for data-independent
+/// addressing.
+fn next_addresses(address: &mut Block, input: &mut Block) {
+    input.0[6] += 1;
+    fill_block(&Block::ZERO, input, address, false);
+    let copy = *address;
+    fill_block(&Block::ZERO, &copy, address, false);
+}
+
+/// The compression function G, applied to `prev` and `reference`. With
+/// `with_xor`, the result is XORed into `next` instead of overwriting it.
+fn fill_block(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut r = *reference;
+    r.xor(prev);
+    let mut tmp = r;
+    if with_xor {
+        tmp.xor(next);
+    }
+
+    let v = &mut r.0;
+    for i in 0..8 {
+        let row: [usize; 16] = core::array::from_fn(|j| 16 * i + j);
+        round(v, &row);
+    }
+    for i in 0..8 {
+        let column: [usize; 16] = core::array::from_fn(|j| 2 * i + (j % 2) + 16 * (j / 2));
+        round(v, &column);
+    }
+
+    tmp.xor(&r);
+    *next = tmp;
+}
+
+/// `BLAKE2_ROUND_NOMSG` from `blamka-round-ref.h` on the given words.
+fn round(v: &mut [u64; QWORDS_IN_BLOCK], i: &[usize; 16]) {
+    g(v, i[0], i[4], i[8], i[12]);
+    g(v, i[1], i[5], i[9], i[13]);
+    g(v, i[2], i[6], i[10], i[14]);
+    g(v, i[3], i[7], i[11], i[15]);
+    g(v, i[0], i[5], i[10], i[15]);
+    g(v, i[1], i[6], i[11], i[12]);
+    g(v, i[2], i[7], i[8], i[13]);
+    g(v, i[3], i[4], i[9], i[14]);
+}
+
+fn g(v: &mut [u64; QWORDS_IN_BLOCK], a: usize, b: usize, c: usize, d: usize) {
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+fn blamka(x: u64, y: u64) -> u64 {
+    let m = u64::from(x as u32) * u64::from(y as u32);
+    x.wrapping_add(y).wrapping_add(m.wrapping_mul(2))
+}
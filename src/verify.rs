@@ -0,0 +1,105 @@
+//! Verification of encoded hashes whose algorithm is only known from the
+//! encoded string itself.
+
+use crate::backend;
+use crate::context::Inputs;
+use crate::error::{Error, Result};
+use crate::phc::{self, PhcHash};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A verifier for a non-Argon2 format, called with the full encoded string
+/// and the candidate password.
+pub type Hook = Box<dyn Fn(&str, &[u8]) -> Result<()> + Send + Sync>;
+
+/// Verifies passwords against encoded hashes of any Argon2 variant and
+/// version, dispatching on the identifier in the encoded string.
+///
+/// Additional formats (e.g. during a migration away from a legacy scheme)
+/// can be plugged in with [`Verifier::register`].
+#[derive(Default)]
+pub struct Verifier {
+    hooks: BTreeMap<String, Hook>,
+    max_threads: Option<u32>,
+}
+
+impl Verifier {
+    pub fn new() -> Verifier {
+        Verifier::default()
+    }
+
+    /// Registers a hook for encoded strings starting with `$<identifier>$`.
+    ///
+    /// Hooks take precedence over the built-in Argon2 verifiers, so they
+    /// may also be used to override them.
+    pub fn register<F>(&mut self, identifier: &str, hook: F) -> &mut Verifier
+    where
+        F: Fn(&str, &[u8]) -> Result<()> + Send + Sync + 'static,
+    {
+        self.hooks.insert(identifier.to_owned(), Box::new(hook));
+        self
+    }
+
+    /// Limits the number of threads that fill the lanes of Argon2 hashes,
+    /// which is one per lane by default, e.g. to verify a `p=8` hash on two
+    /// cores without starting eight threads.
+    ///
+    /// `max_threads` is clamped to `ARGON2_MIN_THREADS..=ARGON2_MAX_THREADS`.
+    /// Builds without threads (the `no-threads` feature, or WebAssembly)
+    /// fail with `Error::ThreadsUnsupported` if it is more than one.
+    pub fn max_threads(&mut self, max_threads: u32) -> &mut Verifier {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    /// Verifies `password` against `encoded`.
+    ///
+    /// Returns `Ok(())` on a match, `ARGON2_VERIFY_MISMATCH` if the password
+    /// is wrong and `Error::UnknownAlgorithm` if there is no verifier for
+    /// the identifier.
+    pub fn verify(&self, encoded: &str, password: &[u8]) -> Result<()> {
+        let identifier = phc::identifier(encoded).unwrap_or("");
+        if let Some(hook) = self.hooks.get(identifier) {
+            return hook(encoded, password);
+        }
+        if phc::type_from_identifier(identifier).is_none() {
+            return Err(Error::UnknownAlgorithm(identifier.to_owned()));
+        }
+        verify_with_threads(&PhcHash::parse(encoded)?, password, self.max_threads)
+    }
+}
+
+impl fmt::Debug for Verifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Verifier")
+            .field("hooks", &self.hooks.keys().collect::<Vec<_>>())
+            .field("max_threads", &self.max_threads)
+            .finish()
+    }
+}
+
+/// Verifies `password` against an encoded Argon2 hash of any variant.
+///
+/// This is a shorthand for [`Verifier::verify`] without any hooks.
+pub fn verify_any(encoded: &str, password: &[u8]) -> Result<()> {
+    Verifier::new().verify(encoded, password)
+}
+
+/// Verifies `password` against an already parsed hash.
+pub fn verify_phc(phc: &PhcHash, password: &[u8]) -> Result<()> {
+    verify_with_threads(phc, password, None)
+}
+
+fn verify_with_threads(phc: &PhcHash, password: &[u8], max_threads: Option<u32>) -> Result<()> {
+    let inputs = Inputs {
+        pwd: password,
+        salt: &phc.salt,
+        max_threads,
+        ..Inputs::default()
+    };
+    backend::verify(&phc.params(), inputs, &phc.hash)
+}
@@ -0,0 +1,96 @@
+#![cfg(all(feature = "alloc", any(argon2_sys_blake2b, argon2_sys_rust_backend)))]
+
+use argon2_sys::blake2b::blake2b_long;
+use argon2_sys::{Blake2b, Error, ARGON2_OUTPUT_TOO_LONG, ARGON2_OUTPUT_TOO_SHORT};
+
+#[test]
+fn hashes_unkeyed() {
+    let mut hasher = Blake2b::new(64).unwrap();
+    hasher.update(b"abc");
+    assert_eq!(
+        hasher.finalize(),
+        hex::decode(
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn hashes_keyed() {
+    let key: Vec<u8> = (0..64).collect();
+    let hasher = Blake2b::with_key(64, &key).unwrap();
+    assert_eq!(
+        hasher.finalize(),
+        hex::decode(
+            "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+             b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn streams_input() {
+    let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let mut whole = Blake2b::new(32).unwrap();
+    whole.update(&data);
+    let mut pieces = Blake2b::new(32).unwrap();
+    for chunk in data.chunks(77) {
+        pieces.update(chunk);
+    }
+    assert_eq!(whole.finalize(), pieces.finalize());
+}
+
+#[test]
+fn rejects_long_outputs() {
+    assert_eq!(
+        Blake2b::new(65).unwrap_err(),
+        Error::Argon2(ARGON2_OUTPUT_TOO_LONG)
+    );
+}
+
+#[test]
+fn finalizes_into_buffers() {
+    let hasher = || {
+        let mut hasher = Blake2b::new(32).unwrap();
+        hasher.update(b"abc");
+        hasher
+    };
+    let mut out = [0u8; 32];
+    hasher().finalize_into(&mut out).unwrap();
+    assert_eq!(out[..], hasher().finalize()[..]);
+    assert_eq!(
+        hasher().finalize_into(&mut [0u8; 31]),
+        Err(Error::Argon2(ARGON2_OUTPUT_TOO_SHORT))
+    );
+    assert_eq!(
+        hasher().finalize_into(&mut [0u8; 33]),
+        Err(Error::Argon2(ARGON2_OUTPUT_TOO_LONG))
+    );
+}
+
+#[test]
+fn hashes_long_outputs() {
+    // Up to 64 bytes, H' is BLAKE2b over the little-endian output length
+    // followed by the input.
+    let mut out = [0u8; 32];
+    blake2b_long(&mut out, b"abc").unwrap();
+    let mut hasher = Blake2b::new(32).unwrap();
+    hasher.update(&32u32.to_le_bytes()).update(b"abc");
+    assert_eq!(out[..], hasher.finalize()[..]);
+
+    let mut out = [0u8; 100];
+    blake2b_long(&mut out, b"abc").unwrap();
+    assert_eq!(
+        out[..],
+        hex::decode(
+            "4c9ba23bcafae5e571a5d41673bb8084a4a1de2688416ed390f669d33d364f3d\
+             4d9bfa7fe762680c6b2362711c4ce5b2c60ddcd14c1277ec1369c79f44c28966\
+             98a2b0773a3ce2e410532fa7c72f0bb61ccca0c24c362f337555cbf2998f2d36\
+             01be70d1"
+        )
+        .unwrap()[..],
+    );
+}
@@ -0,0 +1,38 @@
+use argon2_sys::{build_info, Implementation};
+
+#[test]
+fn matches_the_build_configuration() {
+    let info = build_info();
+    if cfg!(argon2_sys_rust_backend) {
+        assert_eq!(info.implementation, Implementation::Rust);
+    } else if cfg!(argon2_sys_dispatch) {
+        assert_eq!(info.implementation, Implementation::Dispatch);
+    } else if info.pkg_config {
+        assert_eq!(info.implementation, Implementation::Unknown);
+        assert!(info.upstream_version.is_some());
+    } else if cfg!(feature = "simd")
+        || option_env!("ARGON2_SYS_TARGET_CPU").map_or(false, |level| !level.is_empty())
+    {
+        assert_eq!(info.implementation, Implementation::Opt);
+    } else {
+        assert_eq!(info.implementation, Implementation::Ref);
+    }
+    assert_eq!(
+        info.simd_level.is_some(),
+        info.implementation == Implementation::Opt
+    );
+    if cfg!(argon2_sys_no_threads) {
+        assert_eq!(info.threads, Some(false));
+    }
+    assert!(!info.opt_level.is_empty());
+}
+
+#[cfg(not(any(argon2_sys_rust_backend, feature = "system")))]
+#[test]
+fn reports_the_vendored_release() {
+    let info = build_info();
+    assert!(!info.pkg_config);
+    assert_eq!(info.threads, Some(cfg!(not(argon2_sys_no_threads))));
+    let version = info.upstream_version.unwrap();
+    assert!(version.len() == 8 && version.bytes().all(|b| b.is_ascii_digit()));
+}
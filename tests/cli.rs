@@ -0,0 +1,124 @@
+//! Runs the `argon2` binary like the reference tool is run.
+#![cfg(all(feature = "std", not(target_arch = "wasm32")))]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const ENCODED: &str =
+    "$argon2i$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$wWKIMhR9lyDFvRz9YTZweHKfbftvj+qf+YFY4NeBbtA";
+
+fn argon2(args: &[&str], password: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_argon2"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(password.as_bytes()).unwrap();
+    drop(stdin);
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn encoded_only() {
+    let output = argon2(&["somesalt", "-t", "2", "-m", "16", "-e"], "password");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), format!("{}\n", ENCODED));
+}
+
+#[test]
+fn raw_only() {
+    let output = argon2(&["somesalt", "-t", "2", "-k", "65536", "-r"], "password");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "c1628832147d9720c5bd1cfd61367078729f6dfb6f8fea9ff98158e0d7816ed0\n"
+    );
+}
+
+#[test]
+fn full_output() {
+    let output = argon2(&["somesalt", "-t", "2", "-m", "16"], "password");
+    assert!(output.status.success());
+    let lines: Vec<&str> = stdout(&output).lines().collect();
+    assert_eq!(
+        lines[..6],
+        [
+            "Type:\t\tArgon2i",
+            "Iterations:\t2",
+            "Memory:\t\t65536 KiB",
+            "Parallelism:\t1",
+            "Hash:\t\tc1628832147d9720c5bd1cfd61367078729f6dfb6f8fea9ff98158e0d7816ed0",
+            &format!("Encoded:\t{}", ENCODED),
+        ]
+    );
+    assert!(lines[6].ends_with(" seconds"));
+    assert_eq!(lines[7], "Verification ok");
+}
+
+#[test]
+fn type_version_and_parallelism() {
+    let output = argon2(
+        &[
+            "somesalt", "-d", "-t", "2", "-m", "8", "-p", "2", "-v", "10", "-l", "16", "-e",
+        ],
+        "password",
+    );
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("$argon2d$v=16$m=256,t=2,p=2$c29tZXNhbHQ$"));
+}
+
+#[test]
+fn verify() {
+    let output = argon2(&["verify", ENCODED], "password");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Verification ok\n");
+
+    let output = argon2(&["verify", ENCODED], "differentpassword");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stderr(&output),
+        "Error: The password does not match the supplied hash\n"
+    );
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let cases: &[(&[&str], &str)] = &[
+        (&["somesalt", "-e", "-r"], "cannot provide both -e and -r"),
+        (
+            &["somesalt", "-i", "-d"],
+            "cannot specify multiple Argon2 types",
+        ),
+        (
+            &["somesalt", "-m", "16", "-k", "16"],
+            "-m or -k can only be used once",
+        ),
+        (&["somesalt", "-t", "0"], "bad numeric input for -t"),
+        (&["somesalt", "-v", "12"], "invalid Argon2 version"),
+        (&["somesalt", "-x"], "unknown argument"),
+        (&["salt", "-e"], "Salt is too short"),
+    ];
+    for (args, error) in cases {
+        let output = argon2(args, "password");
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert_eq!(stderr(&output), format!("Error: {}\n", error));
+    }
+}
+
+#[test]
+fn rejects_missing_password() {
+    let output = argon2(&["somesalt"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "Error: no password read\n");
+}
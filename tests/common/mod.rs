@@ -0,0 +1,64 @@
+//! Fixtures shared by the integration tests, benchmarks, examples and fuzz
+//! targets. Each of them uses a different subset.
+#![allow(dead_code)]
+
+use argon2_sys::{Argon2_Context, Argon2_id, Params, ARGON2_DEFAULT_FLAGS, ARGON2_VERSION_13};
+
+/// Argon2id with 64 MiB and two passes: small enough for tests, large enough
+/// to fill several segments.
+pub fn params() -> Params {
+    Params {
+        ty: Argon2_id,
+        version: ARGON2_VERSION_13,
+        t_cost: 2,
+        m_cost: 1 << 16,
+        lanes: 1,
+        hash_len: 32,
+    }
+}
+
+/// The byte strings hashed into a tag, all empty by default.
+#[derive(Clone, Copy, Default)]
+pub struct Inputs<'a> {
+    pub pwd: &'a [u8],
+    pub salt: &'a [u8],
+    pub secret: &'a [u8],
+    pub ad: &'a [u8],
+}
+
+/// Passes empty inputs as null, as C callers usually do.
+pub fn ptr(bytes: &[u8]) -> *mut u8 {
+    if bytes.is_empty() {
+        std::ptr::null_mut()
+    } else {
+        bytes.as_ptr() as *mut u8
+    }
+}
+
+/// A context that hashes `inputs` into `out` with the costs in `params`, one
+/// thread per lane, the default flags and no allocation callbacks. The type
+/// goes to `argon2_ctx` separately. The inputs are only read, so callers
+/// that set `ARGON2_FLAG_CLEAR_PASSWORD` or `ARGON2_FLAG_CLEAR_SECRET` must
+/// point `pwd` and `secret` at memory they own.
+pub fn context(params: &Params, inputs: &Inputs<'_>, out: &mut [u8]) -> Argon2_Context {
+    Argon2_Context {
+        out: out.as_mut_ptr(),
+        outlen: out.len() as u32,
+        pwd: ptr(inputs.pwd),
+        pwdlen: inputs.pwd.len() as u32,
+        salt: ptr(inputs.salt),
+        saltlen: inputs.salt.len() as u32,
+        secret: ptr(inputs.secret),
+        secretlen: inputs.secret.len() as u32,
+        ad: ptr(inputs.ad),
+        adlen: inputs.ad.len() as u32,
+        t_cost: params.t_cost,
+        m_cost: params.m_cost,
+        lanes: params.lanes,
+        threads: params.lanes,
+        version: params.version,
+        allocate_cbk: None,
+        free_cbk: None,
+        flags: ARGON2_DEFAULT_FLAGS,
+    }
+}
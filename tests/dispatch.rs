@@ -0,0 +1,24 @@
+#![cfg(argon2_sys_dispatch)]
+
+mod common;
+
+use argon2_sys::{backend, derive_key, force_backend, Backend, Params};
+
+#[test]
+fn every_backend_produces_the_same_output() {
+    let params = Params {
+        lanes: 4,
+        ..common::params()
+    };
+    let detected = Backend::detect();
+    let expected = derive_key::<32>(b"password", b"somesalt", &params).unwrap();
+
+    for &candidate in Backend::ALL.iter().filter(|b| b.is_supported()) {
+        force_backend(candidate);
+        assert_eq!(backend(), candidate);
+        let key = derive_key::<32>(b"password", b"somesalt", &params).unwrap();
+        assert_eq!(key.expose(), expected.expose(), "{:?}", candidate);
+    }
+
+    force_backend(detected);
+}
@@ -0,0 +1,77 @@
+#![cfg(all(feature = "alloc", feature = "getrandom"))]
+
+mod common;
+
+use argon2_sys::{
+    hash_encoded, verify_any, Error, Hasher, Params, PhcHash, ARGON2_MIN_SALT_LENGTH,
+    ARGON2_SALT_TOO_SHORT, DEFAULT_SALT_LEN,
+};
+
+#[test]
+fn generates_random_salts() {
+    let first = hash_encoded(b"password", &Params::default()).unwrap();
+    let second = hash_encoded(b"password", &Params::default()).unwrap();
+    assert_ne!(first, second);
+
+    for encoded in &[first, second] {
+        assert_eq!(verify_any(encoded, b"password"), Ok(()));
+        let phc = PhcHash::parse(encoded).unwrap();
+        assert_eq!(phc.salt.len(), DEFAULT_SALT_LEN);
+        assert!(!phc.needs_rehash(&Params::default()));
+    }
+}
+
+#[test]
+fn uses_the_injected_rng() {
+    let rng = |salt: &mut [u8]| {
+        salt.copy_from_slice(b"somesalt");
+        Ok(())
+    };
+    let mut hasher = Hasher::with_rng(common::params(), rng).salt_len(8);
+    let encoded = hasher.hash_encoded(b"password").unwrap();
+    assert_eq!(
+        encoded,
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc",
+    );
+}
+
+#[test]
+fn rejects_short_salt_lengths() {
+    let min = ARGON2_MIN_SALT_LENGTH as usize;
+    let mut hasher = Hasher::new(Params::default()).salt_len(min - 1);
+    assert_eq!(
+        hasher.hash_encoded(b"password"),
+        Err(Error::Argon2(ARGON2_SALT_TOO_SHORT)),
+    );
+
+    let mut hasher = Hasher::new(Params::default()).salt_len(min);
+    let encoded = hasher.hash_encoded(b"password").unwrap();
+    assert_eq!(PhcHash::parse(&encoded).unwrap().salt.len(), min);
+}
+
+#[test]
+fn thread_count_does_not_change_the_hash() {
+    let params = Params {
+        lanes: 4,
+        ..common::params()
+    };
+    let hasher = Hasher::new(params);
+    let expected = hasher.hash_encoded_with_salt(b"password", b"somesalt");
+    // 0 is clamped to 1.
+    for &max_threads in &[0, 1] {
+        let single = hasher.clone().max_threads(max_threads);
+        assert_eq!(
+            single.hash_encoded_with_salt(b"password", b"somesalt"),
+            expected
+        );
+    }
+
+    let two = hasher
+        .max_threads(2)
+        .hash_encoded_with_salt(b"password", b"somesalt");
+    if cfg!(argon2_sys_no_threads) {
+        assert_eq!(two, Err(Error::ThreadsUnsupported));
+    } else {
+        assert_eq!(two, expected);
+    }
+}
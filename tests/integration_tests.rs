@@ -1,3 +1,5 @@
+#![cfg(not(argon2_sys_rust_backend))]
+
 use argon2_sys::{
     argon2_error_message, argon2_hash, argon2_verify, Argon2_i, Argon2_id, ARGON2_DECODING_FAIL,
     ARGON2_MEMORY_TOO_LITTLE, ARGON2_OK, ARGON2_PWD_PTR_MISMATCH, ARGON2_SALT_TOO_SHORT,
@@ -18,7 +20,10 @@ fn run_hash(t: u32, m: u32, p: u32, password: &str, salt: &str, ty: u32, version
         hash_buffer.as_mut_ptr() as *mut libc::c_void,
         hash_buffer.len(),
     );
-    let (encoded, encodedlen) = (encoded_buffer.as_mut_ptr() as *mut i8, encoded_buffer.len());
+    let (encoded, encodedlen) = (
+        encoded_buffer.as_mut_ptr() as *mut libc::c_char,
+        encoded_buffer.len(),
+    );
     let (pwd, pwdlen) = (
         password.as_bytes().as_ptr() as *const libc::c_void,
         password.len(),
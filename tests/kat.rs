@@ -0,0 +1,71 @@
+#![cfg(feature = "alloc")]
+
+use argon2_sys::rust::kat::genkat;
+use argon2_sys::{Argon2_d, Argon2_i, Argon2_id, ARGON2_VERSION_10, ARGON2_VERSION_13};
+
+/// Embeds a file from `kats/` in the source tree `build.rs` compiles, so
+/// that the comparison also runs where the tests cannot read files, such as
+/// under wasmtime.
+macro_rules! kat {
+    ($name:literal) => {
+        include_str!(concat!(env!("ARGON2_SYS_KATS_DIR"), "/", $name))
+    };
+}
+
+/// Compares line by line, so a mismatch reports the first differing line
+/// rather than two 12k-line strings.
+fn assert_same(actual: &str, expected: &str) {
+    for (number, (a, e)) in actual.lines().zip(expected.lines()).enumerate() {
+        assert_eq!(a, e, "line {}", number + 1);
+    }
+    assert_eq!(actual.lines().count(), expected.lines().count());
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn matches_argon2d() {
+    assert_same(
+        &genkat(Argon2_d, ARGON2_VERSION_13).unwrap(),
+        kat!("argon2d"),
+    );
+}
+
+#[test]
+fn matches_argon2d_v16() {
+    assert_same(
+        &genkat(Argon2_d, ARGON2_VERSION_10).unwrap(),
+        kat!("argon2d_v16"),
+    );
+}
+
+#[test]
+fn matches_argon2i() {
+    assert_same(
+        &genkat(Argon2_i, ARGON2_VERSION_13).unwrap(),
+        kat!("argon2i"),
+    );
+}
+
+#[test]
+fn matches_argon2i_v16() {
+    assert_same(
+        &genkat(Argon2_i, ARGON2_VERSION_10).unwrap(),
+        kat!("argon2i_v16"),
+    );
+}
+
+#[test]
+fn matches_argon2id() {
+    assert_same(
+        &genkat(Argon2_id, ARGON2_VERSION_13).unwrap(),
+        kat!("argon2id"),
+    );
+}
+
+#[test]
+fn matches_argon2id_v16() {
+    assert_same(
+        &genkat(Argon2_id, ARGON2_VERSION_10).unwrap(),
+        kat!("argon2id_v16"),
+    );
+}
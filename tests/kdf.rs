@@ -0,0 +1,64 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+
+use argon2_sys::{
+    derive_key, derive_subkeys, Error, ARGON2_OUTPUT_TOO_LONG, ARGON2_OUTPUT_TOO_SHORT,
+};
+
+#[test]
+fn derives_fixed_size_keys() {
+    let key = derive_key::<32>(b"password", b"somesalt", &common::params()).unwrap();
+    assert_eq!(
+        key.expose()[..],
+        hex::decode("09316115d5cf24ed5a15a31a3ba326e5cf32edc24702987c02b6566f61913cf7").unwrap()[..],
+    );
+
+    let key = derive_key::<64>(b"password", b"somesalt", &common::params()).unwrap();
+    assert_eq!(key.expose().len(), 64);
+}
+
+#[test]
+fn rejects_short_keys() {
+    let result = derive_key::<3>(b"password", b"somesalt", &common::params());
+    assert_eq!(result.unwrap_err(), Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+}
+
+#[test]
+fn derives_labeled_subkeys() {
+    let labels = [("encryption", 32), ("mac", 32), ("check", 16)];
+    let keys = derive_subkeys(b"password", b"somesalt", &common::params(), &labels).unwrap();
+    let lengths: Vec<_> = keys.iter().map(|key| key.expose().len()).collect();
+    assert_eq!(lengths, [32, 32, 16]);
+    assert_ne!(keys[0].expose(), keys[1].expose());
+
+    let again = derive_subkeys(b"password", b"somesalt", &common::params(), &labels).unwrap();
+    assert_eq!(keys[0].expose(), again[0].expose());
+
+    // Renaming a label changes every subkey.
+    let labels = [("encryption", 32), ("mac", 32), ("verify", 16)];
+    let other = derive_subkeys(b"password", b"somesalt", &common::params(), &labels).unwrap();
+    assert_ne!(keys[0].expose(), other[0].expose());
+    assert_ne!(keys[1].expose(), other[1].expose());
+}
+
+#[test]
+fn rejects_subkey_totals_out_of_range() {
+    let derive = |labels: &[(&str, usize)]| {
+        derive_subkeys(b"password", b"somesalt", &common::params(), labels).unwrap_err()
+    };
+    assert_eq!(derive(&[]), Error::Argon2(ARGON2_OUTPUT_TOO_SHORT));
+    assert_eq!(
+        derive(&[("a", 1), ("b", 2)]),
+        Error::Argon2(ARGON2_OUTPUT_TOO_SHORT)
+    );
+    assert_eq!(
+        derive(&[("a", usize::MAX), ("b", 1)]),
+        Error::Argon2(ARGON2_OUTPUT_TOO_LONG)
+    );
+    #[cfg(target_pointer_width = "64")]
+    assert_eq!(
+        derive(&[("a", 1 << 31), ("b", 1 << 31)]),
+        Error::Argon2(ARGON2_OUTPUT_TOO_LONG)
+    );
+}
@@ -0,0 +1,167 @@
+//! Compares the declarations in `src/lib.rs` with `argon2.h` as compiled by
+//! the C compiler, as reported by `src/layout.c`.
+#![cfg(not(argon2_sys_rust_backend))]
+
+use argon2_sys::*;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::mem::{align_of, offset_of, size_of};
+use std::os::raw::{c_char, c_long, c_ulong};
+
+#[repr(C)]
+struct Entry<T> {
+    name: *const c_char,
+    value: T,
+}
+
+extern "C" {
+    fn argon2_sys_layout() -> *const Entry<usize>;
+    fn argon2_sys_signed_constants() -> *const Entry<c_long>;
+    fn argon2_sys_unsigned_constants() -> *const Entry<c_ulong>;
+}
+
+/// Reads a table terminated by a `NULL` name.
+fn read<T: Copy>(table: *const Entry<T>) -> BTreeMap<String, T> {
+    let mut entries = BTreeMap::new();
+    for i in 0.. {
+        let entry = unsafe { &*table.add(i) };
+        if entry.name.is_null() {
+            break;
+        }
+        let name = unsafe { CStr::from_ptr(entry.name) };
+        entries.insert(name.to_str().unwrap().to_owned(), entry.value);
+    }
+    entries
+}
+
+fn table<T>(entries: &[(&str, T)]) -> BTreeMap<String, T>
+where
+    T: Copy,
+{
+    entries
+        .iter()
+        .map(|&(name, value)| (name.to_owned(), value))
+        .collect()
+}
+
+macro_rules! fields {
+    ($($field:ident),*) => {
+        [$((stringify!($field), offset_of!(Argon2_Context, $field))),*]
+    };
+}
+
+macro_rules! values {
+    ($ty:ty: $($constant:ident),*) => {
+        [$((stringify!($constant), $constant as $ty)),*]
+    };
+}
+
+#[test]
+fn context_layout() {
+    let mut expected = table(&fields!(
+        out,
+        outlen,
+        pwd,
+        pwdlen,
+        salt,
+        saltlen,
+        secret,
+        secretlen,
+        ad,
+        adlen,
+        t_cost,
+        m_cost,
+        lanes,
+        threads,
+        version,
+        allocate_cbk,
+        free_cbk,
+        flags
+    ));
+    expected.extend(table(&[
+        ("sizeof(argon2_context)", size_of::<Argon2_Context>()),
+        ("alignof(argon2_context)", align_of::<Argon2_Context>()),
+        ("sizeof(argon2_type)", size_of::<Argon2_type>()),
+        ("sizeof(argon2_version)", size_of::<Argon2_version>()),
+        ("sizeof(argon2_error_codes)", size_of::<Argon2_ErrorCodes>()),
+    ]));
+    assert_eq!(read(unsafe { argon2_sys_layout() }), expected);
+}
+
+#[test]
+fn types_versions_and_error_codes() {
+    let expected = table(&values!(c_long:
+        Argon2_d,
+        Argon2_i,
+        Argon2_id,
+        ARGON2_VERSION_10,
+        ARGON2_VERSION_13,
+        ARGON2_VERSION_NUMBER,
+        ARGON2_OK,
+        ARGON2_OUTPUT_PTR_NULL,
+        ARGON2_OUTPUT_TOO_SHORT,
+        ARGON2_OUTPUT_TOO_LONG,
+        ARGON2_PWD_TOO_SHORT,
+        ARGON2_PWD_TOO_LONG,
+        ARGON2_SALT_TOO_SHORT,
+        ARGON2_SALT_TOO_LONG,
+        ARGON2_AD_TOO_SHORT,
+        ARGON2_AD_TOO_LONG,
+        ARGON2_SECRET_TOO_SHORT,
+        ARGON2_SECRET_TOO_LONG,
+        ARGON2_TIME_TOO_SMALL,
+        ARGON2_TIME_TOO_LARGE,
+        ARGON2_MEMORY_TOO_LITTLE,
+        ARGON2_MEMORY_TOO_MUCH,
+        ARGON2_LANES_TOO_FEW,
+        ARGON2_LANES_TOO_MANY,
+        ARGON2_PWD_PTR_MISMATCH,
+        ARGON2_SALT_PTR_MISMATCH,
+        ARGON2_SECRET_PTR_MISMATCH,
+        ARGON2_AD_PTR_MISMATCH,
+        ARGON2_MEMORY_ALLOCATION_ERROR,
+        ARGON2_FREE_MEMORY_CBK_NULL,
+        ARGON2_ALLOCATE_MEMORY_CBK_NULL,
+        ARGON2_INCORRECT_PARAMETER,
+        ARGON2_INCORRECT_TYPE,
+        ARGON2_OUT_PTR_MISMATCH,
+        ARGON2_THREADS_TOO_FEW,
+        ARGON2_THREADS_TOO_MANY,
+        ARGON2_MISSING_ARGS,
+        ARGON2_ENCODING_FAIL,
+        ARGON2_DECODING_FAIL,
+        ARGON2_THREAD_FAIL,
+        ARGON2_DECODING_LENGTH_FAIL,
+        ARGON2_VERIFY_MISMATCH
+    ));
+    assert_eq!(read(unsafe { argon2_sys_signed_constants() }), expected);
+}
+
+#[test]
+fn limits_and_flags() {
+    let expected = table(&values!(c_ulong:
+        ARGON2_MIN_LANES,
+        ARGON2_MAX_LANES,
+        ARGON2_MIN_THREADS,
+        ARGON2_MAX_THREADS,
+        ARGON2_SYNC_POINTS,
+        ARGON2_MIN_OUTLEN,
+        ARGON2_MAX_OUTLEN,
+        ARGON2_MIN_MEMORY,
+        ARGON2_MAX_MEMORY,
+        ARGON2_MIN_TIME,
+        ARGON2_MAX_TIME,
+        ARGON2_MIN_PWD_LENGTH,
+        ARGON2_MAX_PWD_LENGTH,
+        ARGON2_MIN_AD_LENGTH,
+        ARGON2_MAX_AD_LENGTH,
+        ARGON2_MIN_SALT_LENGTH,
+        ARGON2_MAX_SALT_LENGTH,
+        ARGON2_MIN_SECRET,
+        ARGON2_MAX_SECRET,
+        ARGON2_DEFAULT_FLAGS,
+        ARGON2_FLAG_CLEAR_PASSWORD,
+        ARGON2_FLAG_CLEAR_SECRET
+    ));
+    assert_eq!(read(unsafe { argon2_sys_unsigned_constants() }), expected);
+}
@@ -0,0 +1,106 @@
+//! Runs a workload through the C library under Valgrind's memcheck, which
+//! fails the test on invalid reads and writes, uses of uninitialized memory
+//! and leaks, including in the threads that fill the lanes.
+//!
+//! The test re-runs its own executable under `valgrind`. It is skipped if
+//! `valgrind` is not installed, unless `ARGON2_SYS_MEMCHECK=1` is set to
+//! require it.
+
+use std::env;
+use std::process::Command;
+
+/// Set in the process running under Valgrind.
+const CHILD: &str = "ARGON2_SYS_MEMCHECK_CHILD";
+
+#[cfg(not(argon2_sys_rust_backend))]
+fn workload() {
+    use argon2_sys::*;
+    use std::ffi::CString;
+
+    for &ty in &[Argon2_d, Argon2_i, Argon2_id] {
+        let params = Params {
+            ty,
+            t_cost: 2,
+            m_cost: 256,
+            lanes: 4,
+            ..Params::default()
+        };
+        let encoded = Hasher::new(params).hash_encoded(b"password").unwrap();
+        verify_any(&encoded, b"password").unwrap();
+        assert_eq!(
+            verify_any(&encoded, b"wrong password"),
+            Err(Error::Argon2(ARGON2_VERIFY_MISMATCH))
+        );
+
+        // `argon2_verify` allocates and frees buffers sized by the encoded
+        // string itself.
+        let encoded = CString::new(encoded).unwrap();
+        let code =
+            unsafe { argon2_verify(encoded.as_ptr(), b"password".as_ptr() as *const _, 8, ty) };
+        assert_eq!(code, ARGON2_OK);
+
+        let key = derive_subkeys(b"password", b"somesalt", &params, &[("a", 16), ("b", 48)]);
+        assert_eq!(key.unwrap().len(), 2);
+    }
+
+    // Failures after some allocations have been made: an empty hash is
+    // only rejected once the string has been decoded.
+    let truncated = CString::new("$argon2id$v=19$m=256,t=2,p=4$c29tZXNhbHQ$").unwrap();
+    let code = unsafe {
+        argon2_verify(
+            truncated.as_ptr(),
+            b"password".as_ptr() as *const _,
+            8,
+            Argon2_id,
+        )
+    };
+    assert_eq!(code, ARGON2_OUTPUT_TOO_SHORT);
+    let params = Params {
+        m_cost: 1,
+        ..Params::default()
+    };
+    assert_eq!(
+        Hasher::new(params).hash_encoded(b"password"),
+        Err(Error::Argon2(ARGON2_MEMORY_TOO_LITTLE))
+    );
+
+    #[cfg(argon2_sys_blake2b)]
+    {
+        let mut hasher = Blake2b::with_key(64, b"key").unwrap();
+        hasher.update(b"message");
+        assert_eq!(hasher.finalize().len(), 64);
+    }
+}
+
+#[cfg(argon2_sys_rust_backend)]
+fn workload() {}
+
+fn main() {
+    if cfg!(argon2_sys_rust_backend) || cfg!(target_arch = "wasm32") {
+        return;
+    }
+    if env::var_os(CHILD).is_some() {
+        workload();
+        return;
+    }
+
+    let required =
+        env::var("ARGON2_SYS_MEMCHECK").map_or(false, |value| !value.is_empty() && value != "0");
+    let status = Command::new("valgrind")
+        .args([
+            "--quiet",
+            "--error-exitcode=1",
+            "--leak-check=full",
+            "--show-leak-kinds=definite,indirect",
+            "--errors-for-leak-kinds=definite,indirect",
+            "--track-origins=yes",
+        ])
+        .arg(env::current_exe().unwrap())
+        .env(CHILD, "1")
+        .status();
+    match status {
+        Ok(status) => assert!(status.success(), "memcheck reported errors"),
+        Err(error) if required => panic!("could not run valgrind: {}", error),
+        Err(_) => eprintln!("memcheck: valgrind is not installed, skipping"),
+    }
+}
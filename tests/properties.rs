@@ -0,0 +1,166 @@
+//! Invariants of hashing and encoding over randomly chosen parameters, as
+//! opposed to the fixed cases in `integration_tests.rs`.
+#![cfg(all(
+    feature = "alloc",
+    not(argon2_sys_rust_backend),
+    not(target_arch = "wasm32")
+))]
+
+mod common;
+
+use argon2_sys::{
+    argon2_ctx, argon2_encodedlen, argon2_hash, argon2_verify, Argon2_d, Argon2_i, Argon2_id,
+    Params, PhcHash, ARGON2_OK, ARGON2_VERSION_10, ARGON2_VERSION_13,
+};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+struct Case {
+    ty: u32,
+    version: u32,
+    t_cost: u32,
+    m_cost: u32,
+    lanes: u32,
+    hash_len: usize,
+    pwd: Vec<u8>,
+    salt: Vec<u8>,
+}
+
+/// Small enough costs that each case hashes in well under a millisecond.
+fn case() -> impl Strategy<Value = Case> {
+    (
+        prop_oneof![Just(Argon2_d), Just(Argon2_i), Just(Argon2_id)],
+        prop_oneof![Just(ARGON2_VERSION_10), Just(ARGON2_VERSION_13)],
+        1..=3u32,
+        1..=4u32,
+        4..=100usize,
+        prop::collection::vec(any::<u8>(), 0..=64),
+        prop::collection::vec(any::<u8>(), 8..=64),
+    )
+        .prop_flat_map(|(ty, version, t_cost, lanes, hash_len, pwd, salt)| {
+            (8 * lanes..=64).prop_map(move |m_cost| Case {
+                ty,
+                version,
+                t_cost,
+                m_cost,
+                lanes,
+                hash_len,
+                pwd: pwd.clone(),
+                salt: salt.clone(),
+            })
+        })
+}
+
+/// Returns the raw hash and the encoded string.
+fn hash(case: &Case) -> (Vec<u8>, String) {
+    let encodedlen = unsafe {
+        argon2_encodedlen(
+            case.t_cost,
+            case.m_cost,
+            case.lanes,
+            case.salt.len() as u32,
+            case.hash_len as u32,
+            case.ty,
+        )
+    };
+    let mut hash = vec![0u8; case.hash_len];
+    // Twice the expected length, so that an underestimate is detected
+    // rather than turned into an error.
+    let mut encoded = vec![0u8; 2 * encodedlen];
+    let code = unsafe {
+        argon2_hash(
+            case.t_cost,
+            case.m_cost,
+            case.lanes,
+            case.pwd.as_ptr() as *const _,
+            case.pwd.len(),
+            case.salt.as_ptr() as *const _,
+            case.salt.len(),
+            hash.as_mut_ptr() as *mut _,
+            hash.len(),
+            encoded.as_mut_ptr() as *mut _,
+            encoded.len(),
+            case.ty,
+            case.version,
+        )
+    };
+    assert_eq!(code, ARGON2_OK);
+    let nul = encoded.iter().position(|&byte| byte == 0).unwrap();
+    let encoded = String::from_utf8(encoded[..nul].to_vec()).unwrap();
+    // `argon2_encodedlen` counts the terminating NUL.
+    assert!(
+        encoded.len() < encodedlen,
+        "{} >= {}",
+        encoded.len(),
+        encodedlen
+    );
+    (hash, encoded)
+}
+
+/// Returns the tag for `inputs`, which are the password, salt, secret and
+/// associated data.
+fn tag(ty: u32, inputs: &[Vec<u8>; 4]) -> Vec<u8> {
+    let [pwd, salt, secret, ad] = inputs;
+    let mut out = vec![0u8; 32];
+    let params = Params {
+        ty,
+        t_cost: 1,
+        m_cost: 16,
+        lanes: 2,
+        ..Params::default()
+    };
+    let inputs = common::Inputs {
+        pwd,
+        salt,
+        secret,
+        ad,
+    };
+    let mut context = common::context(&params, &inputs, &mut out);
+    assert_eq!(unsafe { argon2_ctx(&mut context, ty) }, ARGON2_OK);
+    out
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn encoded_hash_verifies(case in case()) {
+        let (_, encoded) = hash(&case);
+        let encoded = std::ffi::CString::new(encoded).unwrap();
+        let code = unsafe {
+            argon2_verify(encoded.as_ptr(), case.pwd.as_ptr() as *const _, case.pwd.len(), case.ty)
+        };
+        prop_assert_eq!(code, ARGON2_OK);
+    }
+
+    #[test]
+    fn encoded_hash_round_trips(case in case()) {
+        let (hash, encoded) = hash(&case);
+        let phc = PhcHash::parse(&encoded).unwrap();
+        prop_assert_eq!(phc.ty, case.ty);
+        prop_assert_eq!(phc.version, case.version);
+        prop_assert_eq!(phc.t_cost, case.t_cost);
+        prop_assert_eq!(phc.m_cost, case.m_cost);
+        prop_assert_eq!(phc.lanes, case.lanes);
+        prop_assert_eq!(&phc.salt, &case.salt);
+        prop_assert_eq!(&phc.hash, &hash);
+        prop_assert_eq!(phc.to_string(), encoded);
+    }
+
+    #[test]
+    fn any_bit_flip_changes_the_tag(
+        ty in prop_oneof![Just(Argon2_d), Just(Argon2_i), Just(Argon2_id)],
+        pwd in prop::collection::vec(any::<u8>(), 1..=32),
+        salt in prop::collection::vec(any::<u8>(), 8..=32),
+        secret in prop::collection::vec(any::<u8>(), 1..=32),
+        ad in prop::collection::vec(any::<u8>(), 1..=32),
+        field in 0..4usize,
+        bit in any::<prop::sample::Index>(),
+    ) {
+        let inputs = [pwd, salt, secret, ad];
+        let mut flipped = inputs.clone();
+        let bit = bit.index(flipped[field].len() * 8);
+        flipped[field][bit / 8] ^= 1 << (bit % 8);
+        prop_assert_ne!(tag(ty, &inputs), tag(ty, &flipped));
+    }
+}
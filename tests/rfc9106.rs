@@ -0,0 +1,162 @@
+//! The test vectors from section 5 of RFC 9106, through `argon2_ctx` with
+//! every context field populated.
+#![cfg(not(argon2_sys_rust_backend))]
+
+mod common;
+
+use argon2_sys::{
+    argon2_ctx, Argon2_Context, Argon2_d, Argon2_i, Argon2_id, Params, ARGON2_DEFAULT_FLAGS,
+    ARGON2_FLAG_CLEAR_PASSWORD, ARGON2_FLAG_CLEAR_SECRET, ARGON2_MEMORY_ALLOCATION_ERROR,
+    ARGON2_OK,
+};
+use std::alloc::{self, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Inputs {
+    pwd: Vec<u8>,
+    salt: Vec<u8>,
+    secret: Vec<u8>,
+    ad: Vec<u8>,
+}
+
+impl Inputs {
+    /// 32 bytes of 0x01, 16 bytes of 0x02 as salt, 8 bytes of 0x03 as
+    /// secret and 12 bytes of 0x04 as associated data.
+    fn rfc9106() -> Inputs {
+        Inputs {
+            pwd: vec![1; 32],
+            salt: vec![2; 16],
+            secret: vec![3; 8],
+            ad: vec![4; 12],
+        }
+    }
+
+    /// 32 KiB, 3 passes, and 4 lanes filled by 4 threads. The clear flags
+    /// wipe `pwd` and `secret`, so they are passed as mutable pointers.
+    fn context(&mut self, out: &mut [u8], flags: u32) -> Argon2_Context {
+        let params = Params {
+            t_cost: 3,
+            m_cost: 32,
+            lanes: 4,
+            ..Params::default()
+        };
+        let inputs = common::Inputs {
+            pwd: &self.pwd,
+            salt: &self.salt,
+            secret: &self.secret,
+            ad: &self.ad,
+        };
+        let context = common::context(&params, &inputs, out);
+        Argon2_Context {
+            pwd: self.pwd.as_mut_ptr(),
+            secret: self.secret.as_mut_ptr(),
+            allocate_cbk: Some(allocate),
+            free_cbk: Some(deallocate),
+            flags,
+            ..context
+        }
+    }
+}
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static FREED: AtomicUsize = AtomicUsize::new(0);
+
+fn layout(bytes: usize) -> Layout {
+    Layout::from_size_align(bytes, 64).unwrap()
+}
+
+unsafe extern "C" fn allocate(memory: *mut *mut u8, bytes: usize) -> libc::c_int {
+    let block = alloc::alloc(layout(bytes));
+    if block.is_null() {
+        return ARGON2_MEMORY_ALLOCATION_ERROR;
+    }
+    ALLOCATED.fetch_add(bytes, Ordering::SeqCst);
+    *memory = block;
+    ARGON2_OK
+}
+
+unsafe extern "C" fn deallocate(memory: *mut u8, bytes: usize) {
+    FREED.fetch_add(bytes, Ordering::SeqCst);
+    alloc::dealloc(memory, layout(bytes));
+}
+
+fn hash(ty: u32, flags: u32) -> (Vec<u8>, Inputs, Argon2_Context) {
+    let mut inputs = Inputs::rfc9106();
+    let mut tag = vec![0u8; 32];
+    let mut context = inputs.context(&mut tag, flags);
+    assert_eq!(unsafe { argon2_ctx(&mut context, ty) }, ARGON2_OK);
+    (tag, inputs, context)
+}
+
+#[test]
+fn argon2d() {
+    let (tag, _, _) = hash(Argon2_d, ARGON2_DEFAULT_FLAGS);
+    assert_eq!(
+        tag,
+        hex::decode("512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb").unwrap(),
+    );
+}
+
+#[test]
+fn argon2i() {
+    let (tag, _, _) = hash(Argon2_i, ARGON2_DEFAULT_FLAGS);
+    assert_eq!(
+        tag,
+        hex::decode("c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8").unwrap(),
+    );
+}
+
+#[test]
+fn argon2id() {
+    let (tag, _, _) = hash(Argon2_id, ARGON2_DEFAULT_FLAGS);
+    assert_eq!(
+        tag,
+        hex::decode("0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659").unwrap(),
+    );
+}
+
+#[test]
+fn uses_the_memory_callbacks() {
+    let before = FREED.load(Ordering::SeqCst);
+    hash(Argon2_id, ARGON2_DEFAULT_FLAGS);
+    // Other tests may allocate concurrently, so only check that at least
+    // the 32 blocks of 1 KiB were handed back.
+    assert!(FREED.load(Ordering::SeqCst) - before >= 32 * 1024);
+    assert!(ALLOCATED.load(Ordering::SeqCst) >= FREED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn leaves_inputs_alone_by_default() {
+    let (_, inputs, context) = hash(Argon2_id, ARGON2_DEFAULT_FLAGS);
+    assert_eq!(inputs.pwd, [1; 32]);
+    assert_eq!(inputs.secret, [3; 8]);
+    assert_eq!((context.pwdlen, context.secretlen), (32, 8));
+}
+
+#[test]
+fn clears_password_and_secret() {
+    let expected = hash(Argon2_id, ARGON2_DEFAULT_FLAGS).0;
+
+    let flags = ARGON2_FLAG_CLEAR_PASSWORD | ARGON2_FLAG_CLEAR_SECRET;
+    let (tag, inputs, context) = hash(Argon2_id, flags);
+    // The buffers are wiped after they have been hashed.
+    assert_eq!(tag, expected);
+    assert_eq!(inputs.pwd, [0; 32]);
+    assert_eq!(inputs.secret, [0; 8]);
+    assert_eq!((context.pwdlen, context.secretlen), (0, 0));
+    assert_eq!(inputs.salt, [2; 16]);
+    assert_eq!(inputs.ad, [4; 12]);
+}
+
+#[test]
+fn clears_only_what_is_asked_for() {
+    let (_, inputs, context) = hash(Argon2_id, ARGON2_FLAG_CLEAR_PASSWORD);
+    assert_eq!(inputs.pwd, [0; 32]);
+    assert_eq!(inputs.secret, [3; 8]);
+    assert_eq!((context.pwdlen, context.secretlen), (0, 8));
+
+    let (_, inputs, context) = hash(Argon2_id, ARGON2_FLAG_CLEAR_SECRET);
+    assert_eq!(inputs.pwd, [1; 32]);
+    assert_eq!(inputs.secret, [0; 8]);
+    assert_eq!((context.pwdlen, context.secretlen), (32, 0));
+}
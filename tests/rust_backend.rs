@@ -0,0 +1,148 @@
+//! Cross-checks the pure-Rust port against the C library.
+#![cfg(all(feature = "alloc", not(argon2_sys_rust_backend)))]
+
+mod common;
+
+use argon2_sys::{
+    argon2_ctx, argon2_error_message, rust, Argon2_d, Argon2_i, Argon2_id, Error, Params,
+    ARGON2_VERSION_10, ARGON2_VERSION_13,
+};
+use common::Inputs;
+use std::ffi::CStr;
+
+fn params(ty: u32, version: u32, t_cost: u32, m_cost: u32, lanes: u32) -> Params {
+    Params {
+        ty,
+        version,
+        t_cost,
+        m_cost,
+        lanes,
+        hash_len: 32,
+    }
+}
+
+fn c_hash(params: &Params, inputs: &Inputs<'_>, out: &mut [u8]) -> Result<(), Error> {
+    let mut context = common::context(params, inputs, out);
+    match unsafe { argon2_ctx(&mut context, params.ty) } {
+        0 => Ok(()),
+        code => Err(Error::Argon2(code)),
+    }
+}
+
+fn assert_same(params: &Params, inputs: &Inputs<'_>, outlen: usize) {
+    let mut expected = vec![0u8; outlen];
+    let c_result = c_hash(params, inputs, &mut expected);
+    let mut actual = vec![0u8; outlen];
+    let rust_result = rust::hash(
+        params,
+        inputs.pwd,
+        inputs.salt,
+        inputs.secret,
+        inputs.ad,
+        &mut actual,
+    );
+    assert_eq!(rust_result, c_result, "{:?}", params);
+    assert_eq!(actual, expected, "{:?}", params);
+}
+
+#[test]
+fn matches_integration_cases() {
+    // (t_cost, log2 of m_cost, lanes, password, salt) for every successful
+    // case in tests/integration_tests.rs.
+    let cases: &[(u32, u32, u32, &str, &str)] = &[
+        (2, 16, 1, "password", "somesalt"),
+        (2, 18, 1, "password", "somesalt"),
+        (2, 8, 1, "password", "somesalt"),
+        (2, 8, 2, "password", "somesalt"),
+        (1, 16, 1, "password", "somesalt"),
+        (4, 16, 1, "password", "somesalt"),
+        (2, 16, 1, "differentpassword", "somesalt"),
+        (2, 16, 1, "password", "diffsalt"),
+    ];
+    for &ty in &[Argon2_d, Argon2_i, Argon2_id] {
+        for &version in &[ARGON2_VERSION_10, ARGON2_VERSION_13] {
+            for &(t_cost, m_log2, lanes, pwd, salt) in cases {
+                let inputs = Inputs {
+                    pwd: pwd.as_bytes(),
+                    salt: salt.as_bytes(),
+                    secret: &[],
+                    ad: &[],
+                };
+                assert_same(
+                    &params(ty, version, t_cost, 1 << m_log2, lanes),
+                    &inputs,
+                    32,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_with_secret_and_associated_data() {
+    let inputs = Inputs {
+        pwd: &[1; 32],
+        salt: &[2; 16],
+        secret: &[3; 8],
+        ad: &[4; 12],
+    };
+    for &ty in &[Argon2_d, Argon2_i, Argon2_id] {
+        // Enough blocks per segment to need a second address block, and
+        // output lengths on both sides of a single BLAKE2b digest.
+        assert_same(&params(ty, ARGON2_VERSION_13, 3, 32, 4), &inputs, 32);
+        assert_same(&params(ty, ARGON2_VERSION_13, 2, 2048, 4), &inputs, 64);
+        assert_same(&params(ty, ARGON2_VERSION_13, 2, 100, 3), &inputs, 100);
+        assert_same(&params(ty, ARGON2_VERSION_10, 1, 8, 1), &inputs, 4);
+    }
+}
+
+#[test]
+fn matches_error_codes() {
+    let inputs = Inputs {
+        pwd: b"password",
+        salt: b"somesalt",
+        secret: &[],
+        ad: &[],
+    };
+    let short_salt = Inputs {
+        salt: b"s",
+        ..inputs
+    };
+    assert_same(&params(Argon2_id, ARGON2_VERSION_13, 2, 1, 1), &inputs, 32);
+    assert_same(&params(Argon2_id, ARGON2_VERSION_13, 2, 16, 3), &inputs, 32);
+    assert_same(
+        &params(Argon2_id, ARGON2_VERSION_13, 0, 1 << 8, 1),
+        &inputs,
+        32,
+    );
+    assert_same(
+        &params(Argon2_id, ARGON2_VERSION_13, 2, 1 << 8, 0),
+        &inputs,
+        32,
+    );
+    assert_same(
+        &params(Argon2_id, ARGON2_VERSION_13, 2, 1 << 8, 1),
+        &short_salt,
+        32,
+    );
+    assert_same(
+        &params(Argon2_id, ARGON2_VERSION_13, 2, 1 << 8, 1),
+        &inputs,
+        3,
+    );
+    assert_same(&params(3, ARGON2_VERSION_13, 2, 1 << 8, 1), &inputs, 32);
+    // The salt is checked before the memory cost.
+    assert_same(
+        &params(Argon2_id, ARGON2_VERSION_13, 2, 1, 1),
+        &short_salt,
+        32,
+    );
+}
+
+#[test]
+fn matches_error_messages() {
+    for code in -40..=1 {
+        let expected = unsafe { CStr::from_ptr(argon2_error_message(code)) };
+        assert_eq!(rust::error_message(code), expected.to_str().unwrap());
+    }
+}
@@ -0,0 +1,208 @@
+#![cfg(all(feature = "alloc", feature = "getrandom"))]
+
+mod common;
+
+use argon2_sys::{
+    verify_any, Argon2_d, Argon2_i, Argon2_id, Error, Hasher, Params, PhcHash, Verifier,
+    ARGON2_DECODING_FAIL, ARGON2_SALT_TOO_SHORT, ARGON2_VERIFY_MISMATCH, ARGON2_VERSION_10,
+    ARGON2_VERSION_13,
+};
+use std::fmt::Write;
+
+fn hash_encoded(password: &str, ty: u32, version: u32) -> String {
+    let params = Params {
+        ty,
+        version,
+        m_cost: 1 << 8,
+        lanes: 2,
+        ..common::params()
+    };
+    Hasher::new(params)
+        .hash_encoded_with_salt(password.as_bytes(), b"somesalt")
+        .unwrap()
+}
+
+#[test]
+fn verifies_every_type_and_version() {
+    for &ty in &[Argon2_d, Argon2_i, Argon2_id] {
+        for &version in &[ARGON2_VERSION_10, ARGON2_VERSION_13] {
+            let encoded = hash_encoded("password", ty, version);
+            assert_eq!(verify_any(&encoded, b"password"), Ok(()), "{}", encoded);
+            assert_eq!(
+                verify_any(&encoded, b"passwore"),
+                Err(Error::Argon2(ARGON2_VERIFY_MISMATCH)),
+                "{}",
+                encoded,
+            );
+        }
+    }
+}
+
+#[test]
+fn verifies_known_hashes() {
+    let encoded =
+        "$argon2i$v=16$m=65536,t=2,p=1$c29tZXNhbHQ$9sTbSlTio3Biev89thdrlKKiCaYsjjYVJxGAL3swxpQ";
+    assert_eq!(verify_any(encoded, b"password"), Ok(()));
+
+    let encoded =
+        "$argon2i$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$wWKIMhR9lyDFvRz9YTZweHKfbftvj+qf+YFY4NeBbtA";
+    assert_eq!(verify_any(encoded, b"password"), Ok(()));
+
+    let encoded =
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc";
+    assert_eq!(verify_any(encoded, b"password"), Ok(()));
+}
+
+#[test]
+fn rejects_malformed_hashes() {
+    // Missing a $
+    let encoded =
+        "$argon2i$v=19$m=65536,t=2,p=1c29tZXNhbHQ$wWKIMhR9lyDFvRz9YTZweHKfbftvj+qf+YFY4NeBbtA";
+    assert_eq!(
+        verify_any(encoded, b"password"),
+        Err(Error::Argon2(ARGON2_DECODING_FAIL)),
+    );
+
+    // Leading zero in a decimal
+    let encoded =
+        "$argon2i$v=19$m=065536,t=2,p=1$c29tZXNhbHQ$wWKIMhR9lyDFvRz9YTZweHKfbftvj+qf+YFY4NeBbtA";
+    assert_eq!(
+        verify_any(encoded, b"password"),
+        Err(Error::Argon2(ARGON2_DECODING_FAIL)),
+    );
+
+    // Salt is too short
+    let encoded = "$argon2i$v=19$m=65536,t=2,p=1$$9sTbSlTio3Biev89thdrlKKiCaYsjjYVJxGAL3swxpQ";
+    assert_eq!(
+        verify_any(encoded, b"password"),
+        Err(Error::Argon2(ARGON2_SALT_TOO_SHORT)),
+    );
+}
+
+#[test]
+fn rejects_unknown_algorithms() {
+    let encoded = "$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW";
+    assert_eq!(
+        verify_any(encoded, b"password"),
+        Err(Error::UnknownAlgorithm("2b".to_owned())),
+    );
+}
+
+#[test]
+fn dispatches_to_registered_hooks() {
+    let mut verifier = Verifier::new();
+    verifier.register("plain", |encoded, password| {
+        if encoded.as_bytes()[7..] == *password {
+            Ok(())
+        } else {
+            Err(Error::Argon2(ARGON2_VERIFY_MISMATCH))
+        }
+    });
+
+    assert_eq!(verifier.verify("$plain$password", b"password"), Ok(()));
+    assert_eq!(
+        verifier.verify("$plain$password", b"passwore"),
+        Err(Error::Argon2(ARGON2_VERIFY_MISMATCH)),
+    );
+
+    let encoded = hash_encoded("password", Argon2_id, ARGON2_VERSION_13);
+    assert_eq!(verifier.verify(&encoded, b"password"), Ok(()));
+}
+
+#[test]
+fn phc_hash_round_trips() {
+    let encoded =
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc";
+    let phc: PhcHash = encoded.parse().unwrap();
+    assert_eq!(phc.ty, Argon2_id);
+    assert_eq!(phc.version, ARGON2_VERSION_13);
+    assert_eq!((phc.m_cost, phc.t_cost, phc.lanes), (65536, 2, 1));
+    assert_eq!(phc.salt, b"somesalt");
+    assert_eq!(phc.to_string(), encoded);
+}
+
+#[test]
+fn refuses_to_format_unknown_types() {
+    let mut phc = PhcHash::parse(
+        "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc",
+    )
+    .unwrap();
+    phc.ty = Argon2_id + 1;
+    assert!(write!(String::new(), "{}", phc).is_err());
+}
+
+#[test]
+fn verifies_hashes_without_version() {
+    let encoded =
+        "$argon2i$m=65536,t=2,p=1$c29tZXNhbHQ$9sTbSlTio3Biev89thdrlKKiCaYsjjYVJxGAL3swxpQ";
+    let phc = PhcHash::parse(encoded).unwrap();
+    assert_eq!(phc.version, ARGON2_VERSION_10);
+    assert_eq!(phc.to_string(), encoded);
+    assert_eq!(verify_any(encoded, b"password"), Ok(()));
+
+    // The encoded password is "passwore"
+    let encoded =
+        "$argon2i$m=65536,t=2,p=1$c29tZXNhbHQ$b2G3seW+uPzerwQQC+/E1K50CLLO7YXy0JRcaTuswRo";
+    assert_eq!(
+        verify_any(encoded, b"password"),
+        Err(Error::Argon2(ARGON2_VERIFY_MISMATCH)),
+    );
+    assert_eq!(verify_any(encoded, b"passwore"), Ok(()));
+}
+
+#[test]
+fn flags_outdated_hashes_for_rehash() {
+    let encoded =
+        "$argon2i$m=65536,t=2,p=1$c29tZXNhbHQ$9sTbSlTio3Biev89thdrlKKiCaYsjjYVJxGAL3swxpQ";
+    let phc = PhcHash::parse(encoded).unwrap();
+
+    let policy = Params {
+        ty: Argon2_i,
+        ..common::params()
+    };
+    assert!(phc.needs_rehash(&policy));
+    assert!(phc.needs_rehash(&Params::default()));
+
+    let policy = Params {
+        version: ARGON2_VERSION_10,
+        ..policy
+    };
+    assert!(!phc.needs_rehash(&policy));
+}
+
+#[test]
+fn verifies_with_fewer_threads_than_lanes() {
+    let params = Params {
+        m_cost: 1 << 8,
+        lanes: 8,
+        ..Params::default()
+    };
+    let encoded = Hasher::new(params).hash_encoded(b"password").unwrap();
+    let mut verifier = Verifier::new();
+    // 0 is clamped to 1.
+    for &max_threads in &[0, 1] {
+        assert_eq!(
+            verifier
+                .max_threads(max_threads)
+                .verify(&encoded, b"password"),
+            Ok(())
+        );
+    }
+    for &max_threads in &[2, u32::MAX] {
+        let expected = if cfg!(argon2_sys_no_threads) {
+            Err(Error::ThreadsUnsupported)
+        } else {
+            Ok(())
+        };
+        assert_eq!(
+            verifier
+                .max_threads(max_threads)
+                .verify(&encoded, b"password"),
+            expected
+        );
+    }
+    assert_eq!(
+        verifier.max_threads(1).verify(&encoded, b"wrong password"),
+        Err(Error::Argon2(ARGON2_VERIFY_MISMATCH))
+    );
+}